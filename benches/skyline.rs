@@ -0,0 +1,183 @@
+//! Query benchmarks over random graphs. Run with `cargo bench`, and again with `--features fast-hash` to compare the
+//! hashers of the keyword searches.
+//!
+//! `criterion` is not a dependency of this crate, so this is a plain timing harness reporting the median of a few
+//! runs per configuration.
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use petgraph::prelude::DiGraphMap;
+
+const RUNS: usize = 5;
+
+/// Deterministic xorshift generator, so every run benchmarks the same graphs.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u32) -> u32 {
+        (self.next() % bound as u64) as u32
+    }
+}
+
+/// Builds a random graph of `num_nodes` nodes with `degree` out-edges each, where every node holds a keyword out
+/// of `num_keywords` with probability `1 / keyword_rarity`.
+fn build_benchmark_graph(
+    num_nodes: u32,
+    degree: u32,
+    num_keywords: u32,
+    keyword_rarity: u32,
+) -> (DiGraphMap<u32, ()>, HashMap<u32, Vec<u32>>) {
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let mut graph = DiGraphMap::new();
+    for source in 0..num_nodes {
+        for _ in 0..degree {
+            graph.add_edge(source, rng.below(num_nodes), ());
+        }
+    }
+    let mut node_to_keyword = HashMap::new();
+    for node in 0..num_nodes {
+        if rng.below(keyword_rarity) == 0 {
+            node_to_keyword.insert(node, vec![rng.below(num_keywords)]);
+        }
+    }
+    (graph, node_to_keyword)
+}
+
+fn median_time(mut f: impl FnMut()) -> Duration {
+    let mut times: Vec<_> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect();
+    times.sort_unstable();
+    times[RUNS / 2]
+}
+
+/// Builds `num_points` anti-correlated points in `dims` dimensions, most of which are on the skyline.
+fn build_benchmark_points(num_points: usize, dims: usize) -> Vec<Vec<u32>> {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    (0..num_points)
+        .map(|_| {
+            let mut point: Vec<_> = (0..dims - 1).map(|_| rng.below(1_000_000)).collect();
+            let sum: u32 = point.iter().sum();
+            point.push((dims as u32 - 1) * 1_000_000 - sum + rng.below(1_000));
+            point
+        })
+        .collect()
+}
+
+fn main() {
+    let hasher = if cfg!(feature = "fast-hash") {
+        "Fx"
+    } else {
+        "SipHash"
+    };
+    println!("keyword search hasher: {}", hasher);
+    for num_points in [2_000, 10_000] {
+        let points = build_benchmark_points(num_points, 3);
+        let serial = median_time(|| {
+            black_box(skyline::skyline(&points));
+        });
+        print!("points {:>7}  skyline {:>10.3?}", num_points, serial);
+        #[cfg(feature = "parallel")]
+        {
+            assert_eq!(
+                skyline::skyline(&points),
+                skyline::skyline_parallel(&points)
+            );
+            let parallel = median_time(|| {
+                black_box(skyline::skyline_parallel(&points));
+            });
+            print!("  skyline_parallel {:>10.3?}", parallel);
+        }
+        println!();
+    }
+
+    for num_nodes in [1_000, 10_000, 100_000] {
+        let (graph, node_to_keyword) = build_benchmark_graph(num_nodes, 4, 8, 50);
+        let csr = skyline::CsrGraph::from_digraphmap(&graph).unwrap();
+        for num_keywords in [2, 4] {
+            let keywords: Vec<_> = (0..num_keywords).collect();
+            let distances = median_time(|| {
+                black_box(skyline::keyword_distances::<_, _, u32>(
+                    &graph,
+                    &node_to_keyword,
+                    &keywords,
+                ));
+            });
+            let query = median_time(|| {
+                black_box(skyline::semantic_place_skyline::<_, _, u32>(
+                    &graph,
+                    &node_to_keyword,
+                    &keywords,
+                ));
+            });
+            let csr_query = median_time(|| {
+                black_box(
+                    skyline::semantic_place_skyline_csr::<_, _, u32>(
+                        &csr,
+                        &node_to_keyword,
+                        &keywords,
+                    )
+                    .unwrap(),
+                );
+            });
+            print!(
+                "nodes {:>7}  keywords {}  keyword_distances {:>10.3?}  semantic_place_skyline {:>10.3?}  \
+                 semantic_place_skyline_csr {:>10.3?}",
+                num_nodes, num_keywords, distances, query, csr_query
+            );
+            #[cfg(feature = "parallel")]
+            {
+                let parallel_distances = median_time(|| {
+                    black_box(skyline::keyword_distances_parallel::<_, _, u32>(
+                        &graph,
+                        &node_to_keyword,
+                        &keywords,
+                    ));
+                });
+                print!("  keyword_distances_parallel {:>10.3?}", parallel_distances);
+            }
+            println!();
+        }
+    }
+
+    // wide queries, where the per-node vectors of a distance map dominate
+    for num_nodes in [1_000, 5_000] {
+        let num_keywords = 50;
+        let (graph, node_to_keyword) = build_benchmark_graph(num_nodes, 4, num_keywords, 2);
+        let keywords: Vec<_> = (0..num_keywords).collect();
+        let query = median_time(|| {
+            black_box(skyline::semantic_place_skyline::<_, _, u32>(
+                &graph,
+                &node_to_keyword,
+                &keywords,
+            ));
+        });
+        let flat_query = median_time(|| {
+            black_box(
+                skyline::semantic_place_skyline_flat::<_, _, u32>(
+                    &graph,
+                    &node_to_keyword,
+                    &keywords,
+                )
+                .unwrap(),
+            );
+        });
+        println!(
+            "nodes {:>7}  keywords {}  semantic_place_skyline {:>10.3?}  semantic_place_skyline_flat {:>10.3?}",
+            num_nodes, num_keywords, query, flat_query
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Compact graph representation for read-only query workloads.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use num_traits::bounds::UpperBounded;
+use num_traits::{One, Zero};
+use petgraph::graphmap::{DiGraphMap, NodeTrait};
+use petgraph::Direction;
+
+use crate::{bfs_from, relax_min, skyline, Skyline, SkylineError};
+
+/// Read-only compressed sparse row copy of a graph, with nodes renumbered `0..node_count()`. Neighbor lists are
+/// contiguous slices, which makes the keyword search much cheaper than on a [`DiGraphMap`].
+#[derive(Clone, Debug)]
+pub struct CsrGraph<N> {
+    nodes: Vec<N>,
+    index: HashMap<N, u32>,
+    out_offsets: Vec<usize>,
+    out_targets: Vec<u32>,
+    in_offsets: Vec<usize>,
+    in_sources: Vec<u32>,
+}
+
+impl<N: NodeTrait> CsrGraph<N> {
+    /// Copies `graph`, keeping its node order. Fails with [`SkylineError::TooManyNodes`] if the nodes do not fit
+    /// `u32` indices.
+    pub fn from_digraphmap<E>(graph: &DiGraphMap<N, E>) -> Result<Self, SkylineError> {
+        let nodes: Vec<_> = graph.nodes().collect();
+        let index = nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, &node)| {
+                let idx = u32::try_from(idx)
+                    .map_err(|_| SkylineError::TooManyNodes { count: nodes.len() })?;
+                Ok((node, idx))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let (out_offsets, out_targets) =
+            Self::adjacency(graph, &nodes, &index, Direction::Outgoing);
+        let (in_offsets, in_sources) = Self::adjacency(graph, &nodes, &index, Direction::Incoming);
+        Ok(CsrGraph {
+            nodes,
+            index,
+            out_offsets,
+            out_targets,
+            in_offsets,
+            in_sources,
+        })
+    }
+
+    fn adjacency<E>(
+        graph: &DiGraphMap<N, E>,
+        nodes: &[N],
+        index: &HashMap<N, u32>,
+        direction: Direction,
+    ) -> (Vec<usize>, Vec<u32>) {
+        let mut offsets = Vec::with_capacity(nodes.len() + 1);
+        let mut neighbors = Vec::with_capacity(graph.edge_count());
+        offsets.push(0);
+        for &node in nodes {
+            neighbors.extend(
+                graph
+                    .neighbors_directed(node, direction)
+                    .map(|nbr| index[&nbr]),
+            );
+            offsets.push(neighbors.len());
+        }
+        (offsets, neighbors)
+    }
+
+    /// Number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Node id of index `idx`.
+    pub fn node(&self, idx: u32) -> N {
+        self.nodes[idx as usize]
+    }
+
+    /// Index of node `node`, if it is part of the graph.
+    pub fn index_of(&self, node: N) -> Option<u32> {
+        self.index.get(&node).copied()
+    }
+
+    /// Indices of the neighbors of index `idx` in `direction`.
+    pub fn neighbors_directed(&self, idx: u32, direction: Direction) -> &[u32] {
+        let (offsets, neighbors) = match direction {
+            Direction::Outgoing => (&self.out_offsets, &self.out_targets),
+            Direction::Incoming => (&self.in_offsets, &self.in_sources),
+        };
+        &neighbors[offsets[idx as usize]..offsets[idx as usize + 1]]
+    }
+}
+
+/// Like [`semantic_place_skyline`](crate::semantic_place_skyline), but over a [`CsrGraph`]. Distances are kept
+/// in one flat row-major array of `node_count() * keywords.len()` entries instead of a hash map.
+pub fn semantic_place_skyline_csr<N, K, D>(
+    graph: &CsrGraph<N>,
+    node_to_keyword: &HashMap<N, Vec<K>>,
+    keywords: &[K],
+) -> Result<Skyline<N, D>, SkylineError>
+where
+    N: NodeTrait + Hash,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let num_keywords = keywords.len();
+    let mut dists = vec![D::max_value(); graph.node_count() * num_keywords];
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        let sources = node_to_keyword
+            .iter()
+            .filter(|(_, node_keywords)| node_keywords.binary_search(keyword).is_ok())
+            .filter_map(|(&node, _)| graph.index_of(node));
+        row_major_bfs(&mut dists, num_keywords, keyword_idx, sources, |current| {
+            graph
+                .neighbors_directed(current, Direction::Incoming)
+                .iter()
+                .copied()
+        });
+    }
+    let points: Vec<_> = dists.chunks(num_keywords).collect();
+    Ok(skyline(&points)
+        .into_iter()
+        .map(|idx| (graph.nodes[idx], points[idx].to_vec()))
+        .collect())
+}
+
+/// Search shared by the row-major distance layouts, here and in [`keyword_distances_flat`]: fills column
+/// `keyword_idx` of `dists`, which holds `num_keywords` entries per row, with the distance from each row to the
+/// nearest of the rows `sources`. `neighbors(row)` are the rows one hop farther from the sources.
+///
+/// [`keyword_distances_flat`]: crate::keyword_distances_flat
+pub(crate) fn row_major_bfs<D, I>(
+    dists: &mut [D],
+    num_keywords: usize,
+    keyword_idx: usize,
+    sources: impl IntoIterator<Item = u32>,
+    neighbors: impl FnMut(u32) -> I,
+) where
+    D: Copy + Ord + Zero + One + UpperBounded,
+    I: IntoIterator<Item = u32>,
+{
+    let queue = sources
+        .into_iter()
+        .map(|row| {
+            dists[row as usize * num_keywords + keyword_idx] = D::zero();
+            (row, D::zero(), ())
+        })
+        .collect();
+    bfs_from(queue, D::max_value(), neighbors, |_, _, nbr, dist| {
+        relax_min(&mut dists[nbr as usize * num_keywords + keyword_idx], dist)
+    });
+}
@@ -0,0 +1,152 @@
+//! Shared query handle for concurrent services.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use num_traits::bounds::UpperBounded;
+use num_traits::{One, Zero};
+use petgraph::graphmap::{DiGraphMap, NodeTrait};
+
+use crate::{semantic_place_skyline, Skyline, SkylineError};
+
+/// Data change applied by [`SkylineEngine::apply_updates`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Update<N, K> {
+    /// Adds edge `source -> target`, and its endpoints if needed.
+    AddEdge(N, N),
+    /// Removes edge `source -> target` if present. The endpoints are kept.
+    RemoveEdge(N, N),
+    /// Adds a keyword to a node, and the node to the graph if needed.
+    AddKeyword(N, K),
+    /// Removes a keyword from a node if present.
+    RemoveKeyword(N, K),
+}
+
+struct Data<N, K> {
+    graph: DiGraphMap<N, ()>,
+    node_to_keyword: HashMap<N, Vec<K>>,
+}
+
+/// Graph and node keywords behind a [`RwLock`], so that queries run concurrently while an update waits for them
+/// and blocks new ones. Share it between threads with an [`Arc`](std::sync::Arc).
+pub struct SkylineEngine<N, K> {
+    data: RwLock<Data<N, K>>,
+}
+
+impl<N: NodeTrait, K: Ord> SkylineEngine<N, K> {
+    /// Wraps `graph` and `node_to_keyword`. Each entry of `node_to_keyword` should be sorted.
+    pub fn new(graph: DiGraphMap<N, ()>, node_to_keyword: HashMap<N, Vec<K>>) -> Self {
+        SkylineEngine {
+            data: RwLock::new(Data {
+                graph,
+                node_to_keyword,
+            }),
+        }
+    }
+
+    /// Runs [`semantic_place_skyline`] under the read lock, failing with [`SkylineError::EmptyKeywords`] instead of
+    /// panicking if `keywords` is empty.
+    pub fn query<D>(&self, keywords: &[K]) -> Result<Skyline<N, D>, SkylineError>
+    where
+        D: Copy + Ord + Zero + One + UpperBounded,
+    {
+        // at least one keyword should be provided
+        if keywords.is_empty() {
+            return Err(SkylineError::EmptyKeywords);
+        }
+        let data = self.data.read().unwrap();
+        Ok(semantic_place_skyline(
+            &data.graph,
+            &data.node_to_keyword,
+            keywords,
+        ))
+    }
+
+    /// Applies `updates` in order under a single write lock, so queries see either none or all of them.
+    pub fn apply_updates(&self, updates: impl IntoIterator<Item = Update<N, K>>) {
+        let mut data = self.data.write().unwrap();
+        for update in updates {
+            match update {
+                Update::AddEdge(source, target) => {
+                    data.graph.add_edge(source, target, ());
+                }
+                Update::RemoveEdge(source, target) => {
+                    data.graph.remove_edge(source, target);
+                }
+                Update::AddKeyword(node, keyword) => {
+                    data.graph.add_node(node);
+                    let node_keywords = data.node_to_keyword.entry(node).or_default();
+                    // keep the keywords sorted
+                    if let Err(idx) = node_keywords.binary_search(&keyword) {
+                        node_keywords.insert(idx, keyword);
+                    }
+                }
+                Update::RemoveKeyword(node, keyword) => {
+                    if let Some(node_keywords) = data.node_to_keyword.get_mut(&node) {
+                        if let Ok(idx) = node_keywords.binary_search(&keyword) {
+                            node_keywords.remove(idx);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{random_graph, sorted, XorShift};
+
+    #[test]
+    fn queries_match_skyline_across_updates() {
+        let mut rng = XorShift::new(157);
+        let keywords = [0, 1, 2];
+        for _ in 0..10 {
+            let (mut graph, mut node_to_keyword) = random_graph(&mut rng, 40, 70, 3);
+            let engine = SkylineEngine::new(graph.clone(), node_to_keyword.clone());
+            for _ in 0..5 {
+                assert_eq!(
+                    engine.query::<u32>(&keywords).map(sorted),
+                    Ok(sorted(semantic_place_skyline(
+                        &graph,
+                        &node_to_keyword,
+                        &keywords
+                    )))
+                );
+                // mirror each update on the local copy
+                let mut updates = Vec::new();
+                for _ in 0..6 {
+                    let (u, v) = (rng.below(45), rng.below(45));
+                    let keyword = rng.below(3);
+                    let update = match rng.below(4) {
+                        0 => {
+                            graph.add_edge(u, v, ());
+                            Update::AddEdge(u, v)
+                        }
+                        1 => {
+                            graph.remove_edge(u, v);
+                            Update::RemoveEdge(u, v)
+                        }
+                        2 => {
+                            graph.add_node(u);
+                            let node_keywords = node_to_keyword.entry(u).or_default();
+                            if let Err(idx) = node_keywords.binary_search(&keyword) {
+                                node_keywords.insert(idx, keyword);
+                            }
+                            Update::AddKeyword(u, keyword)
+                        }
+                        _ => {
+                            if let Some(node_keywords) = node_to_keyword.get_mut(&u) {
+                                node_keywords.retain(|&k| k != keyword);
+                            }
+                            Update::RemoveKeyword(u, keyword)
+                        }
+                    };
+                    updates.push(update);
+                }
+                engine.apply_updates(updates);
+            }
+        }
+    }
+}
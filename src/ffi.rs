@@ -0,0 +1,238 @@
+//! C interface, enabled by the `cffi` feature.
+//!
+//! Build a shared library with `cargo rustc --release --features cffi --lib --crate-type cdylib`.
+//!
+//! Every pointer returned by a `skyline_*_new`/`skyline_query` function is owned by the caller and must be
+//! released exactly once with the matching `skyline_*_free` function. Pointers returned by the result accessors
+//! are borrowed from the result and stay valid until the result is freed; they must not be freed themselves.
+
+use std::collections::HashMap;
+use std::{ptr, slice};
+
+use petgraph::prelude::DiGraphMap;
+
+/// Opaque graph handle.
+pub struct SkylineGraph {
+    graph: DiGraphMap<u32, ()>,
+    node_to_keyword: HashMap<u32, Vec<u32>>,
+}
+
+/// Opaque query result handle.
+pub struct SkylineResult {
+    nodes: Vec<u32>,
+    distances: Vec<u32>,
+    num_keywords: usize,
+}
+
+unsafe fn slice_from_raw<'a, T>(data: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    }
+}
+
+/// Builds a graph from flat arrays.
+///
+/// Edge `i` goes from `edge_src[i]` to `edge_dst[i]`, for `i < num_edges`. Keywords are given in CSR layout: node
+/// `keyword_nodes[j]` holds `keyword_values[keyword_offsets[j]..keyword_offsets[j + 1]]`, for
+/// `j < num_keyword_nodes`, so `keyword_offsets` has `num_keyword_nodes + 1` entries.
+///
+/// Returns null if the offsets are not non-decreasing. Otherwise the returned graph is owned by the caller and
+/// must be released with [`skyline_graph_free`]. The input arrays are copied and can be freed right away.
+///
+/// # Safety
+///
+/// Every array must be valid for reads of the length described above (pointers may be null for zero lengths).
+#[no_mangle]
+pub unsafe extern "C" fn skyline_graph_new(
+    edge_src: *const u32,
+    edge_dst: *const u32,
+    num_edges: usize,
+    keyword_nodes: *const u32,
+    keyword_offsets: *const usize,
+    keyword_values: *const u32,
+    num_keyword_nodes: usize,
+) -> *mut SkylineGraph {
+    let edge_src = slice_from_raw(edge_src, num_edges);
+    let edge_dst = slice_from_raw(edge_dst, num_edges);
+    let keyword_nodes = slice_from_raw(keyword_nodes, num_keyword_nodes);
+    let keyword_offsets = if num_keyword_nodes == 0 {
+        &[]
+    } else {
+        slice_from_raw(keyword_offsets, num_keyword_nodes + 1)
+    };
+    if keyword_offsets.windows(2).any(|w| w[0] > w[1]) {
+        return ptr::null_mut();
+    }
+    let keyword_values =
+        slice_from_raw(keyword_values, keyword_offsets.last().copied().unwrap_or(0));
+
+    let mut graph = DiGraphMap::new();
+    for (&source, &target) in edge_src.iter().zip(edge_dst) {
+        graph.add_edge(source, target, ());
+    }
+    let mut node_to_keyword: HashMap<_, Vec<_>> = HashMap::new();
+    for (&node, range) in keyword_nodes.iter().zip(keyword_offsets.windows(2)) {
+        graph.add_node(node);
+        node_to_keyword
+            .entry(node)
+            .or_default()
+            .extend_from_slice(&keyword_values[range[0]..range[1]]);
+    }
+    for keywords in node_to_keyword.values_mut() {
+        keywords.sort_unstable();
+    }
+    Box::into_raw(Box::new(SkylineGraph {
+        graph,
+        node_to_keyword,
+    }))
+}
+
+/// Releases a graph returned by [`skyline_graph_new`]. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `graph` must be null or a pointer returned by [`skyline_graph_new`] that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn skyline_graph_free(graph: *mut SkylineGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// Runs a skyline query for `keywords[..num_keywords]`.
+///
+/// Returns null if `graph` is null or no keyword is given. Otherwise the returned result is owned by the caller
+/// and must be released with [`skyline_result_free`]. The keyword array is only read during the call.
+///
+/// # Safety
+///
+/// `graph` must be null or a live pointer returned by [`skyline_graph_new`], and `keywords` must be valid for reads
+/// of `num_keywords` elements.
+#[no_mangle]
+pub unsafe extern "C" fn skyline_query(
+    graph: *const SkylineGraph,
+    keywords: *const u32,
+    num_keywords: usize,
+) -> *mut SkylineResult {
+    let Some(graph) = graph.as_ref() else {
+        return ptr::null_mut();
+    };
+    if num_keywords == 0 {
+        return ptr::null_mut();
+    }
+    let keywords = slice_from_raw(keywords, num_keywords);
+    let skyline =
+        crate::semantic_place_skyline::<_, _, u32>(&graph.graph, &graph.node_to_keyword, keywords);
+    let mut nodes = Vec::with_capacity(skyline.len());
+    let mut distances = Vec::with_capacity(skyline.len() * num_keywords);
+    for (node, dist) in skyline {
+        nodes.push(node);
+        distances.extend(dist);
+    }
+    Box::into_raw(Box::new(SkylineResult {
+        nodes,
+        distances,
+        num_keywords,
+    }))
+}
+
+/// Number of skyline nodes in `result`.
+///
+/// # Safety
+///
+/// `result` must be a live pointer returned by [`skyline_query`].
+#[no_mangle]
+pub unsafe extern "C" fn skyline_result_len(result: *const SkylineResult) -> usize {
+    (*result).nodes.len()
+}
+
+/// Number of keywords, i.e. distances per node, in `result`.
+///
+/// # Safety
+///
+/// `result` must be a live pointer returned by [`skyline_query`].
+#[no_mangle]
+pub unsafe extern "C" fn skyline_result_num_keywords(result: *const SkylineResult) -> usize {
+    (*result).num_keywords
+}
+
+/// Skyline node ids, `skyline_result_len` entries.
+///
+/// The array is borrowed from `result`: it stays valid until [`skyline_result_free`] and must not be freed by the
+/// caller.
+///
+/// # Safety
+///
+/// `result` must be a live pointer returned by [`skyline_query`].
+#[no_mangle]
+pub unsafe extern "C" fn skyline_result_nodes(result: *const SkylineResult) -> *const u32 {
+    (*result).nodes.as_ptr()
+}
+
+/// Row-major distance matrix, `skyline_result_len * skyline_result_num_keywords` entries. Row `i` belongs to node
+/// `i` of [`skyline_result_nodes`]; unreachable keywords are reported as `UINT32_MAX`.
+///
+/// The array is borrowed from `result`: it stays valid until [`skyline_result_free`] and must not be freed by the
+/// caller.
+///
+/// # Safety
+///
+/// `result` must be a live pointer returned by [`skyline_query`].
+#[no_mangle]
+pub unsafe extern "C" fn skyline_result_distances(result: *const SkylineResult) -> *const u32 {
+    (*result).distances.as_ptr()
+}
+
+/// Releases a result returned by [`skyline_query`], together with the arrays borrowed from it. Passing null is a
+/// no-op.
+///
+/// # Safety
+///
+/// `result` must be null or a pointer returned by [`skyline_query`] that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn skyline_result_free(result: *mut SkylineResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_fills_the_result_buffers() {
+        // 0 -> 1 -> 2, node 2 holds keyword 7 and node 0 keyword 8
+        let (edge_src, edge_dst) = ([0, 1], [1, 2]);
+        let (keyword_nodes, keyword_offsets, keyword_values) = ([2, 0], [0, 1, 2], [7, 8]);
+        unsafe {
+            let graph = skyline_graph_new(
+                edge_src.as_ptr(),
+                edge_dst.as_ptr(),
+                edge_src.len(),
+                keyword_nodes.as_ptr(),
+                keyword_offsets.as_ptr(),
+                keyword_values.as_ptr(),
+                keyword_nodes.len(),
+            );
+            assert!(!graph.is_null());
+            let keywords = [7, 8];
+            let result = skyline_query(graph, keywords.as_ptr(), keywords.len());
+            assert!(!result.is_null());
+            let len = skyline_result_len(result);
+            assert_eq!(skyline_result_num_keywords(result), 2);
+            let nodes = slice_from_raw(skyline_result_nodes(result), len);
+            let distances = slice_from_raw(skyline_result_distances(result), len * 2);
+            let mut rows: Vec<_> = nodes.iter().copied().zip(distances.chunks(2)).collect();
+            rows.sort_unstable();
+            assert_eq!(rows, [(0, &[2, 0][..]), (2, &[0, u32::MAX][..])]);
+            skyline_result_free(result);
+            // no keyword and no graph are reported as null
+            assert!(skyline_query(graph, ptr::null(), 0).is_null());
+            assert!(skyline_query(ptr::null(), keywords.as_ptr(), keywords.len()).is_null());
+            skyline_graph_free(graph);
+        }
+    }
+}
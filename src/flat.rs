@@ -0,0 +1,151 @@
+//! Keyword distances in a single flat buffer, for queries with many keywords.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use num_traits::bounds::UpperBounded;
+use num_traits::{One, Zero};
+use petgraph::visit::{IntoNeighborsDirected, IntoNodeIdentifiers};
+use petgraph::Direction;
+
+use crate::csr::row_major_bfs;
+use crate::{skyline, Skyline, SkylineError};
+
+/// Keyword distances of every node in one row-major buffer of `node_count() * num_keywords()` entries, instead of a
+/// vector per node as in [`DistanceMap`](crate::DistanceMap).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlatDistances<N, D> {
+    nodes: Vec<N>,
+    num_keywords: usize,
+    dists: Vec<D>,
+}
+
+impl<N: Copy, D> FlatDistances<N, D> {
+    /// Number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of keyword columns.
+    pub fn num_keywords(&self) -> usize {
+        self.num_keywords
+    }
+
+    /// Node of row `idx`.
+    pub fn node(&self, idx: usize) -> N {
+        self.nodes[idx]
+    }
+
+    /// Distances of row `idx`.
+    pub fn row(&self, idx: usize) -> &[D] {
+        &self.dists[idx * self.num_keywords..(idx + 1) * self.num_keywords]
+    }
+
+    /// Iterates over the nodes and their distances, in row order.
+    pub fn rows(&self) -> impl Iterator<Item = (N, &[D])> + '_ {
+        self.nodes
+            .iter()
+            .copied()
+            .zip(self.dists.chunks_exact(self.num_keywords))
+    }
+}
+
+/// Like [`keyword_distances`](crate::keyword_distances), but into a [`FlatDistances`]. Rows follow the order of
+/// `graph.node_identifiers()`. Fails with [`SkylineError::EmptyKeywords`], since rows would have no width, and with
+/// [`SkylineError::TooManyNodes`] if the rows do not fit `u32` indices.
+pub fn keyword_distances_flat<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> Result<FlatDistances<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let num_keywords = keywords.len();
+    let nodes: Vec<_> = graph.node_identifiers().collect();
+    // rows are numbered by u32 indices
+    let num_rows = u32::try_from(nodes.len())
+        .map_err(|_| SkylineError::TooManyNodes { count: nodes.len() })?;
+    let index: HashMap<_, _> = nodes.iter().copied().zip(0..num_rows).collect();
+    let mut dists = vec![D::max_value(); nodes.len() * num_keywords];
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        let sources = nodes.iter().zip(0..num_rows).filter_map(|(node, idx)| {
+            let node_keywords = node_to_keyword.get(node)?;
+            node_keywords.binary_search(keyword).is_ok().then_some(idx)
+        });
+        row_major_bfs(&mut dists, num_keywords, keyword_idx, sources, |current| {
+            graph
+                .neighbors_directed(nodes[current as usize], Direction::Incoming)
+                .map(|nbr| index[&nbr])
+        });
+    }
+    Ok(FlatDistances {
+        nodes,
+        num_keywords,
+        dists,
+    })
+}
+
+/// Returns the rows of `dists` not dominated by any other row, by running [`skyline`] over the rows, which are
+/// slices of the flat buffer, so no row is copied.
+pub fn skyline_flat<N: Copy, D: Ord>(dists: &FlatDistances<N, D>) -> Vec<usize> {
+    let rows: Vec<_> = dists.dists.chunks_exact(dists.num_keywords).collect();
+    skyline(&rows)
+}
+
+/// Like [`semantic_place_skyline`](crate::semantic_place_skyline), but over [`FlatDistances`], which saves a vector
+/// allocation per node and keeps the dominance checks on contiguous memory.
+pub fn semantic_place_skyline_flat<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    let dists = keyword_distances_flat(graph, node_to_keyword, keywords)?;
+    Ok(skyline_flat(&dists)
+        .into_iter()
+        .map(|idx| (dists.node(idx), dists.row(idx).to_vec()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic_place_skyline;
+    use crate::tests::{random_graph, reference_distances, sorted, XorShift};
+
+    #[test]
+    fn flat_distances_match_reference() {
+        let mut rng = XorShift::new(11);
+        for _ in 0..20 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 50, 110, 4);
+            let keywords = [0, 1, 3];
+            let expected = reference_distances(&graph, &node_to_keyword, &keywords);
+            let dists =
+                keyword_distances_flat::<_, _, u32>(&graph, &node_to_keyword, &keywords).unwrap();
+            for (node, row) in dists.rows() {
+                assert_eq!(row, expected[&node]);
+            }
+            assert_eq!(
+                sorted(semantic_place_skyline_flat(&graph, &node_to_keyword, &keywords).unwrap()),
+                sorted(semantic_place_skyline::<_, _, u32>(
+                    &graph,
+                    &node_to_keyword,
+                    &keywords
+                ))
+            );
+        }
+    }
+}
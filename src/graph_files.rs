@@ -0,0 +1,371 @@
+//! GraphML and Graphviz DOT input for the command line tool. Both formats carry the node keywords in a `keywords`
+//! attribute, written like a line of the node keyword file, e.g. `keywords="1,2,3"`. Node ids should be integers.
+//! Edge weights are checked to be numbers but not used, as the graph is unweighted.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use petgraph::prelude::DiGraphMap;
+use skyline::KeywordInterner;
+
+use crate::{add_edge, parse_keyword_list, BuildResult};
+
+/// Graph and node keywords read so far, shared by both formats.
+struct Builder<'a> {
+    graph: DiGraphMap<u32, ()>,
+    node_to_keyword: HashMap<u32, Vec<u32>>,
+    interner: Option<&'a mut KeywordInterner>,
+}
+
+impl Builder<'_> {
+    fn node(&mut self, id: &str) -> Result<u32> {
+        let node = id
+            .parse()
+            .map_err(|_| anyhow!("expect an integer node id, got '{}'", id))?;
+        self.graph.add_node(node);
+        Ok(node)
+    }
+
+    fn keywords(&mut self, node: u32, keywords: &str) -> Result<()> {
+        let keywords = parse_keyword_list(keywords, self.interner.as_deref_mut())?;
+        if self.node_to_keyword.insert(node, keywords).is_some() {
+            return Err(anyhow!("duplicate keywords for node {}.", node));
+        }
+        Ok(())
+    }
+
+    fn edge(
+        &mut self,
+        source: u32,
+        target: u32,
+        weight: Option<&str>,
+        directed: bool,
+    ) -> Result<()> {
+        if let Some(weight) = weight {
+            weight
+                .parse::<f64>()
+                .map_err(|_| anyhow!("expect a numeric edge weight, got '{}'", weight))?;
+        }
+        add_edge(&mut self.graph, source, target)?;
+        if !directed && source != target {
+            add_edge(&mut self.graph, target, source)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a GraphML file. Undirected graphs get an edge in each direction.
+pub fn read_graphml(path: &Path, interner: Option<&mut KeywordInterner>) -> BuildResult {
+    let text = fs::read_to_string(path)?;
+    let mut builder = Builder {
+        graph: DiGraphMap::new(),
+        node_to_keyword: HashMap::new(),
+        interner,
+    };
+    // ids of the node keyword and edge weight attributes
+    let mut keywords_key = None;
+    let mut weight_key = None;
+    let mut directed = true;
+    let mut current_node = None;
+    let mut current_edge: Option<(u32, u32, Option<String>, bool)> = None;
+    let mut data_key: Option<String> = None;
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find('<') {
+        let text = &rest[..start];
+        rest = &rest[start..];
+        if let Some(key) = &data_key {
+            if Some(key) == keywords_key.as_ref() {
+                if let Some(node) = current_node {
+                    builder.keywords(node, &unescape(text.trim()))?;
+                }
+            } else if Some(key) == weight_key.as_ref() {
+                if let Some(edge) = &mut current_edge {
+                    edge.2 = Some(unescape(text.trim()));
+                }
+            }
+        }
+        // skip comments, the declaration and the doctype
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").ok_or(anyhow!("unterminated comment"))?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+        let end = rest.find('>').ok_or(anyhow!("unterminated tag"))?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            match name.trim() {
+                "node" => current_node = None,
+                "edge" => {
+                    if let Some((source, target, weight, edge_directed)) = current_edge.take() {
+                        builder.edge(source, target, weight.as_deref(), edge_directed)?;
+                    }
+                }
+                "data" => data_key = None,
+                _ => {}
+            }
+            continue;
+        }
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let (name, attrs) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let attrs = parse_attributes(attrs)?;
+        let attr = |key: &str| {
+            attrs
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str())
+        };
+        match name {
+            "key" => {
+                let id = attr("id").ok_or(anyhow!("expect an id on every key"))?;
+                match (attr("for"), attr("attr.name")) {
+                    (Some("node"), Some("keywords")) => keywords_key = Some(id.to_owned()),
+                    (Some("edge"), Some("weight")) => weight_key = Some(id.to_owned()),
+                    _ => {}
+                }
+            }
+            "graph" => directed = attr("edgedefault") != Some("undirected"),
+            "node" => {
+                let id = attr("id").ok_or(anyhow!("expect an id on every node"))?;
+                let node = builder.node(id)?;
+                current_node = (!self_closing).then_some(node);
+            }
+            "edge" => {
+                let source = attr("source").ok_or(anyhow!("expect a source on every edge"))?;
+                let target = attr("target").ok_or(anyhow!("expect a target on every edge"))?;
+                let edge_directed = attr("directed").map_or(directed, |value| value == "true");
+                let edge = (
+                    builder.node(source)?,
+                    builder.node(target)?,
+                    None,
+                    edge_directed,
+                );
+                if self_closing {
+                    builder.edge(edge.0, edge.1, None, edge.3)?;
+                } else {
+                    current_edge = Some(edge);
+                }
+            }
+            "data" if !self_closing => data_key = attr("key").map(str::to_owned),
+            _ => {}
+        }
+    }
+    Ok((builder.graph, builder.node_to_keyword))
+}
+
+/// Parses `name="value"` pairs, with single or double quotes.
+fn parse_attributes(mut attrs: &str) -> Result<Vec<(String, String)>> {
+    let mut parsed = Vec::new();
+    loop {
+        attrs = attrs.trim_start();
+        if attrs.is_empty() {
+            return Ok(parsed);
+        }
+        let (name, rest) = attrs
+            .split_once('=')
+            .ok_or(anyhow!("expect name=\"value\", got '{}'", attrs))?;
+        let rest = rest.trim_start();
+        let quote = rest
+            .chars()
+            .next()
+            .filter(|&c| c == '"' || c == '\'')
+            .ok_or(anyhow!(
+                "expect a quoted value for attribute '{}'",
+                name.trim()
+            ))?;
+        let end = rest[1..]
+            .find(quote)
+            .ok_or(anyhow!("unterminated value of attribute '{}'", name.trim()))?;
+        parsed.push((name.trim().to_owned(), unescape(&rest[1..end + 1])));
+        attrs = &rest[end + 2..];
+    }
+}
+
+/// Replaces the predefined XML entities.
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// Identifier, number or quoted string.
+    Id(String),
+    /// One of `{ } [ ] ; , = -> --`.
+    Symbol(&'static str),
+}
+
+fn tokenize_dot(text: &str) -> Result<Vec<Token>> {
+    const SYMBOLS: [&str; 9] = ["->", "--", "{", "}", "[", "]", ";", ",", "="];
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start();
+        // skip comments and preprocessor lines
+        if rest.starts_with("//") || rest.starts_with('#') {
+            rest = rest.find('\n').map_or("", |end| &rest[end..]);
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment.find("*/").ok_or(anyhow!("unterminated comment"))?;
+            rest = &comment[end + 2..];
+            continue;
+        }
+        let Some(c) = rest.chars().next() else {
+            return Ok(tokens);
+        };
+        if let Some(&symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        } else if c == '"' {
+            let mut value = String::new();
+            let mut chars = rest[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((idx, '"')) => break idx + 1,
+                    Some((_, '\\')) => {
+                        if let Some((_, escaped)) = chars.next() {
+                            if escaped != '"' {
+                                value.push('\\');
+                            }
+                            value.push(escaped);
+                        }
+                    }
+                    Some((_, c)) => value.push(c),
+                    None => return Err(anyhow!("unterminated string")),
+                }
+            };
+            tokens.push(Token::Id(value));
+            rest = &rest[end + 1..];
+        } else if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' {
+            // a leading '-' is the sign of a number, edge operators were matched above
+            let end = rest[1..]
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .map_or(rest.len(), |end| end + 1);
+            tokens.push(Token::Id(rest[..end].to_owned()));
+            rest = &rest[end..];
+        } else {
+            return Err(anyhow!("unexpected character '{}'", c));
+        }
+    }
+}
+
+/// Reads a Graphviz DOT file. Subgraphs are flattened into the graph, `graph` files get an edge in each direction.
+pub fn read_dot(path: &Path, interner: Option<&mut KeywordInterner>) -> BuildResult {
+    let tokens = tokenize_dot(&fs::read_to_string(path)?)?;
+    let mut builder = Builder {
+        graph: DiGraphMap::new(),
+        node_to_keyword: HashMap::new(),
+        interner,
+    };
+    let mut tokens = tokens.into_iter().peekable();
+    // header: [strict] (graph | digraph) [id] {
+    let mut directed = None;
+    for token in tokens.by_ref() {
+        match token {
+            Token::Id(id) if id.eq_ignore_ascii_case("digraph") => directed = Some(true),
+            Token::Id(id) if id.eq_ignore_ascii_case("graph") => directed = Some(false),
+            Token::Id(_) => {}
+            Token::Symbol("{") => break,
+            Token::Symbol(symbol) => {
+                return Err(anyhow!("unexpected '{}' before the graph body", symbol))
+            }
+        }
+    }
+    let directed = directed.ok_or(anyhow!("expect 'graph' or 'digraph'"))?;
+    while let Some(token) = tokens.next() {
+        let id = match token {
+            Token::Id(id) => id,
+            // subgraph bodies and statement separators
+            Token::Symbol("{" | "}" | ";" | ",") => continue,
+            Token::Symbol(symbol) => return Err(anyhow!("unexpected '{}'", symbol)),
+        };
+        if id.eq_ignore_ascii_case("subgraph") {
+            // the optional subgraph id
+            if matches!(tokens.peek(), Some(Token::Id(_))) {
+                tokens.next();
+            }
+            continue;
+        }
+        let is_default = ["graph", "node", "edge"]
+            .iter()
+            .any(|keyword| id.eq_ignore_ascii_case(keyword));
+        match tokens.peek() {
+            // graph attribute such as rankdir=LR
+            Some(Token::Symbol("=")) => {
+                tokens.next();
+                tokens.next();
+                continue;
+            }
+            // default attributes do not describe any node or edge
+            Some(Token::Symbol("[")) if is_default => {
+                parse_dot_attributes(&mut tokens)?;
+                continue;
+            }
+            _ => {}
+        }
+        let mut nodes = vec![builder.node(&id)?];
+        while let Some(Token::Symbol("->" | "--")) = tokens.peek() {
+            tokens.next();
+            match tokens.next() {
+                Some(Token::Id(id)) => nodes.push(builder.node(&id)?),
+                _ => return Err(anyhow!("expect a node after an edge operator")),
+            }
+        }
+        let attrs = match tokens.peek() {
+            Some(Token::Symbol("[")) => parse_dot_attributes(&mut tokens)?,
+            _ => Vec::new(),
+        };
+        let attr = |key: &str| {
+            attrs
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str())
+        };
+        if let [node] = nodes[..] {
+            if let Some(keywords) = attr("keywords") {
+                builder.keywords(node, keywords)?;
+            }
+        }
+        for edge in nodes.windows(2) {
+            builder.edge(edge[0], edge[1], attr("weight"), directed)?;
+        }
+    }
+    Ok((builder.graph, builder.node_to_keyword))
+}
+
+/// Parses one or more `[name=value, ...]` lists.
+fn parse_dot_attributes(
+    tokens: &mut std::iter::Peekable<impl Iterator<Item = Token>>,
+) -> Result<Vec<(String, String)>> {
+    let mut attrs = Vec::new();
+    while let Some(Token::Symbol("[")) = tokens.peek() {
+        tokens.next();
+        loop {
+            match tokens.next() {
+                Some(Token::Symbol("]")) => break,
+                Some(Token::Symbol("," | ";")) => {}
+                Some(Token::Id(name)) => {
+                    if tokens.next() != Some(Token::Symbol("=")) {
+                        return Err(anyhow!("expect '=' after attribute '{}'", name));
+                    }
+                    match tokens.next() {
+                        Some(Token::Id(value)) => attrs.push((name, value)),
+                        _ => return Err(anyhow!("expect a value for attribute '{}'", name)),
+                    }
+                }
+                _ => return Err(anyhow!("unterminated attribute list")),
+            }
+        }
+    }
+    Ok(attrs)
+}
@@ -0,0 +1,371 @@
+//! Maintenance of computed keyword distances under data updates.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+use num_traits::bounds::UpperBounded;
+use num_traits::{One, Zero};
+use petgraph::graphmap::{DiGraphMap, NodeTrait};
+use petgraph::visit::{IntoNeighborsDirected, IntoNodeIdentifiers};
+use petgraph::Direction;
+
+use crate::{keywords_in, minimal_elements, multi_source_bfs, resume_bfs, DistanceMap};
+
+/// Removes `keyword` from `node` in `node_to_keyword` and repairs `dists`, the distances for `keywords` computed
+/// by [`keyword_distances`](crate::keyword_distances), then returns the new skyline.
+///
+/// Distances may grow when a source disappears, which local relaxation cannot repair, so the affected column is
+/// recomputed by a full multi-source BFS: O(n + m) on top of the O(n²) skyline filter. Nothing is recomputed if
+/// `node` did not contain `keyword` or `keyword` is not queried.
+pub fn on_keyword_removed<G, K, D>(
+    dists: &mut DistanceMap<G::NodeId, D>,
+    graph: G,
+    node_to_keyword: &mut HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    node: G::NodeId,
+    keyword: &K,
+) -> Vec<(G::NodeId, Vec<D>)>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    let removed = node_to_keyword.get_mut(&node).is_some_and(|node_keywords| {
+        node_keywords
+            .binary_search(keyword)
+            .map(|idx| node_keywords.remove(idx))
+            .is_ok()
+    });
+    let columns = keywords.iter().enumerate().filter(|(_, k)| *k == keyword);
+    if removed {
+        let keywords_of = keywords_in(node_to_keyword);
+        for (keyword_idx, keyword) in columns {
+            for du in dists.values_mut() {
+                du[keyword_idx] = D::max_value();
+            }
+            let sources = graph
+                .node_identifiers()
+                .filter(|&node| keywords_of(node).binary_search(keyword).is_ok());
+            multi_source_bfs(
+                graph,
+                dists,
+                keyword_idx,
+                sources,
+                D::max_value(),
+                &[Direction::Incoming],
+            );
+        }
+    }
+    minimal_elements(dists)
+}
+
+/// Repairs `dists`, the distances computed by [`keyword_distances`](crate::keyword_distances), after `node` gained
+/// the keyword of column `keyword_idx`, then returns the new skyline. The caller updates its `node_to_keyword`.
+///
+/// Distances can only shrink when a source appears, so a BFS seeded at `node` that stops at nodes it does not
+/// improve repairs the column; its cost is bounded by the region that moved closer to the keyword.
+pub fn on_keyword_added<G, D>(
+    dists: &mut DistanceMap<G::NodeId, D>,
+    graph: G,
+    node: G::NodeId,
+    keyword_idx: usize,
+) -> Vec<(G::NodeId, Vec<D>)>
+where
+    G: IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    if dists[&node][keyword_idx] != D::zero() {
+        multi_source_bfs(
+            graph,
+            dists,
+            keyword_idx,
+            [node],
+            D::max_value(),
+            &[Direction::Incoming],
+        );
+    }
+    minimal_elements(dists)
+}
+
+/// Adds edge `source -> target` to `graph` and repairs `dists`, the distances computed by
+/// [`keyword_distances`](crate::keyword_distances), then returns the new skyline.
+///
+/// Distances can only shrink, so a BFS from `source` that stops at nodes it does not improve repairs each column.
+///
+/// # Panics
+///
+/// Panics if `source` or `target` has no distances; add new nodes with [`on_node_added`] first.
+pub fn on_edge_added<N, E, D>(
+    dists: &mut DistanceMap<N, D>,
+    graph: &mut DiGraphMap<N, E>,
+    source: N,
+    target: N,
+    weight: E,
+) -> Vec<(N, Vec<D>)>
+where
+    N: NodeTrait,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    assert!(dists.contains_key(&source) && dists.contains_key(&target));
+    graph.add_edge(source, target, weight);
+    let num_keywords = dists[&source].len();
+    for keyword_idx in 0..num_keywords {
+        let target_dist = dists[&target][keyword_idx];
+        if target_dist == D::max_value() || target_dist + D::one() >= dists[&source][keyword_idx] {
+            continue;
+        }
+        dists.get_mut(&source).unwrap()[keyword_idx] = target_dist + D::one();
+        resume_bfs(
+            &*graph,
+            dists,
+            keyword_idx,
+            [source],
+            D::max_value(),
+            &[Direction::Incoming],
+        );
+    }
+    minimal_elements(dists)
+}
+
+/// Removes edge `source -> target` from `graph` if present and repairs `dists`, the distances computed by
+/// [`keyword_distances`](crate::keyword_distances), then returns the new skyline.
+///
+/// Only the nodes whose every shortest path used the edge are searched again.
+pub fn on_edge_removed<N, E, D>(
+    dists: &mut DistanceMap<N, D>,
+    graph: &mut DiGraphMap<N, E>,
+    source: N,
+    target: N,
+) -> Vec<(N, Vec<D>)>
+where
+    N: NodeTrait,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    if graph.remove_edge(source, target).is_some() {
+        let num_keywords = dists[&source].len();
+        for keyword_idx in 0..num_keywords {
+            repair_increase(graph, dists, keyword_idx, [source]);
+        }
+    }
+    minimal_elements(dists)
+}
+
+/// Adds `node` without edges to `graph` with the sorted `node_keywords`, and its distances for `keywords` to
+/// `dists`, then returns the new skyline. An existing node is left unchanged.
+pub fn on_node_added<N, E, K, D>(
+    dists: &mut DistanceMap<N, D>,
+    graph: &mut DiGraphMap<N, E>,
+    node_to_keyword: &mut HashMap<N, Vec<K>>,
+    keywords: &[K],
+    node: N,
+    node_keywords: Vec<K>,
+) -> Vec<(N, Vec<D>)>
+where
+    N: NodeTrait,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    if !graph.contains_node(node) {
+        graph.add_node(node);
+        let dist = keywords
+            .iter()
+            .map(|keyword| {
+                if node_keywords.binary_search(keyword).is_ok() {
+                    D::zero()
+                } else {
+                    D::max_value()
+                }
+            })
+            .collect();
+        dists.insert(node, dist);
+        node_to_keyword.insert(node, node_keywords);
+    }
+    minimal_elements(dists)
+}
+
+/// Removes `node`, its edges and its keywords, and repairs `dists`, the distances computed by
+/// [`keyword_distances`](crate::keyword_distances), then returns the new skyline.
+///
+/// Only the nodes whose every shortest path went through `node` are searched again.
+pub fn on_node_removed<N, E, K, D>(
+    dists: &mut DistanceMap<N, D>,
+    graph: &mut DiGraphMap<N, E>,
+    node_to_keyword: &mut HashMap<N, Vec<K>>,
+    node: N,
+) -> Vec<(N, Vec<D>)>
+where
+    N: NodeTrait,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    if let Some(dist) = dists.remove(&node) {
+        // the predecessors may have lost their shortest paths
+        let predecessors: Vec<_> = graph
+            .neighbors_directed(node, Direction::Incoming)
+            .filter(|&nbr| nbr != node)
+            .collect();
+        graph.remove_node(node);
+        node_to_keyword.remove(&node);
+        for keyword_idx in 0..dist.len() {
+            repair_increase(graph, dists, keyword_idx, predecessors.iter().copied());
+        }
+    }
+    minimal_elements(dists)
+}
+
+/// Repairs column `keyword_idx` of `dists` after edges were removed out of `candidates`.
+///
+/// First, in increasing distance, collects the affected nodes: a candidate is affected unless it is a source or
+/// still has a successor outside the affected set one hop closer, and the predecessors that were one hop behind an
+/// affected node become candidates. Then the affected nodes are reset and searched again by a Dijkstra seeded
+/// from their unaffected successors, so the cost is bounded by the affected region and its boundary.
+fn repair_increase<N, E, D>(
+    graph: &DiGraphMap<N, E>,
+    dists: &mut DistanceMap<N, D>,
+    keyword_idx: usize,
+    candidates: impl IntoIterator<Item = N>,
+) where
+    N: NodeTrait,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    let dist = |dists: &DistanceMap<N, D>, node: N| dists[&node][keyword_idx];
+    let mut heap: BinaryHeap<_> = candidates
+        .into_iter()
+        .map(|node| Reverse((dist(dists, node), node)))
+        .collect();
+    let mut decided = HashSet::new();
+    let mut affected = HashSet::new();
+    while let Some(Reverse((node_dist, node))) = heap.pop() {
+        if node_dist == D::zero() || node_dist == D::max_value() || !decided.insert(node) {
+            continue;
+        }
+        let supported = graph
+            .neighbors_directed(node, Direction::Outgoing)
+            .any(|nbr| {
+                let nbr_dist = dist(dists, nbr);
+                !affected.contains(&nbr)
+                    && nbr_dist != D::max_value()
+                    && nbr_dist + D::one() == node_dist
+            });
+        if supported {
+            continue;
+        }
+        affected.insert(node);
+        for nbr in graph.neighbors_directed(node, Direction::Incoming) {
+            let nbr_dist = dist(dists, nbr);
+            if nbr_dist == node_dist + D::one() {
+                heap.push(Reverse((nbr_dist, nbr)));
+            }
+        }
+    }
+
+    for node in &affected {
+        dists.get_mut(node).unwrap()[keyword_idx] = D::max_value();
+    }
+    for &node in &affected {
+        let best = graph
+            .neighbors_directed(node, Direction::Outgoing)
+            .filter(|nbr| !affected.contains(nbr))
+            .map(|nbr| dist(dists, nbr))
+            .filter(|&nbr_dist| nbr_dist != D::max_value())
+            .min();
+        if let Some(best) = best {
+            dists.get_mut(&node).unwrap()[keyword_idx] = best + D::one();
+            heap.push(Reverse((best + D::one(), node)));
+        }
+    }
+    while let Some(Reverse((node_dist, node))) = heap.pop() {
+        if node_dist > dist(dists, node) {
+            continue;
+        }
+        for nbr in graph.neighbors_directed(node, Direction::Incoming) {
+            if affected.contains(&nbr) && node_dist + D::one() < dist(dists, nbr) {
+                dists.get_mut(&nbr).unwrap()[keyword_idx] = node_dist + D::one();
+                heap.push(Reverse((node_dist + D::one(), nbr)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyword_distances;
+    use crate::tests::{random_graph, sorted, Graph, NodeToKeyword, XorShift};
+
+    const KEYWORDS: [u32; 3] = [0, 1, 2];
+
+    /// Checks repaired distances and the returned skyline against a recomputation.
+    fn assert_repaired(
+        dists: &DistanceMap<u32, u32>,
+        skyline: Vec<(u32, Vec<u32>)>,
+        graph: &Graph,
+        node_to_keyword: &NodeToKeyword,
+    ) {
+        let expected = keyword_distances(graph, node_to_keyword, &KEYWORDS);
+        assert_eq!(dists, &expected);
+        assert_eq!(sorted(skyline), sorted(minimal_elements(&expected)));
+    }
+
+    #[test]
+    fn keyword_updates_match_recompute() {
+        let mut rng = XorShift::new(31);
+        for _ in 0..10 {
+            let (graph, mut node_to_keyword) = random_graph(&mut rng, 40, 90, 3);
+            let mut dists = keyword_distances(&graph, &node_to_keyword, &KEYWORDS);
+            for _ in 0..20 {
+                let node = rng.below(40);
+                let keyword = rng.below(3);
+                let node_keywords = node_to_keyword.entry(node).or_default();
+                let skyline = match node_keywords.binary_search(&keyword) {
+                    Ok(_) => on_keyword_removed(
+                        &mut dists,
+                        &graph,
+                        &mut node_to_keyword,
+                        &KEYWORDS,
+                        node,
+                        &keyword,
+                    ),
+                    Err(idx) => {
+                        node_keywords.insert(idx, keyword);
+                        on_keyword_added(&mut dists, &graph, node, keyword as usize)
+                    }
+                };
+                assert_repaired(&dists, skyline, &graph, &node_to_keyword);
+            }
+        }
+    }
+
+    #[test]
+    fn graph_updates_match_recompute() {
+        let mut rng = XorShift::new(32);
+        for _ in 0..10 {
+            let (mut graph, mut node_to_keyword) = random_graph(&mut rng, 30, 60, 3);
+            let mut dists = keyword_distances(&graph, &node_to_keyword, &KEYWORDS);
+            for _ in 0..40 {
+                let (source, target) = (rng.below(35), rng.below(35));
+                let skyline = match rng.below(4) {
+                    0 | 1 if dists.contains_key(&source) && dists.contains_key(&target) => {
+                        if graph.contains_edge(source, target) {
+                            on_edge_removed(&mut dists, &mut graph, source, target)
+                        } else {
+                            on_edge_added(&mut dists, &mut graph, source, target, ())
+                        }
+                    }
+                    2 => on_node_added(
+                        &mut dists,
+                        &mut graph,
+                        &mut node_to_keyword,
+                        &KEYWORDS,
+                        source,
+                        vec![target % 3],
+                    ),
+                    _ => on_node_removed(&mut dists, &mut graph, &mut node_to_keyword, source),
+                };
+                assert_repaired(&dists, skyline, &graph, &node_to_keyword);
+            }
+        }
+    }
+}
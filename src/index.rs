@@ -0,0 +1,337 @@
+//! Precomputed keyword distances shared by many queries.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use num_traits::bounds::UpperBounded;
+use num_traits::{One, Zero};
+use petgraph::visit::{IntoNeighborsDirected, IntoNodeIdentifiers};
+use petgraph::Direction;
+
+use crate::{
+    keyword_distances, minimal_elements, multi_source_bfs, DistanceMap, Skyline, SkylineError,
+};
+
+/// Distances from every node to every keyword of a graph, computed once so that queries over any subset of the
+/// keywords only select columns instead of searching the graph again.
+///
+/// Memory grows with the number of nodes times the number of distinct keywords, so this suits graphs with a modest
+/// keyword vocabulary.
+#[derive(Clone, Debug)]
+pub struct KeywordDistanceIndex<N, K, D> {
+    /// Sorted keywords, one distance column each.
+    keywords: Vec<K>,
+    dists: DistanceMap<N, D>,
+}
+
+impl<N, K, D> KeywordDistanceIndex<N, K, D>
+where
+    N: Copy + Hash + Ord,
+    K: Clone + Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    /// Computes the distances from each node of `graph` to each keyword of `node_to_keyword`. Each entry of
+    /// `node_to_keyword` should be sorted.
+    pub fn new<G>(graph: G, node_to_keyword: &HashMap<N, Vec<K>>) -> Self
+    where
+        G: IntoNodeIdentifiers<NodeId = N> + IntoNeighborsDirected,
+    {
+        let mut keywords: Vec<_> = node_to_keyword.values().flatten().cloned().collect();
+        keywords.sort_unstable();
+        keywords.dedup();
+        let dists = keyword_distances(graph, node_to_keyword, &keywords);
+        KeywordDistanceIndex { keywords, dists }
+    }
+
+    /// Indexed keywords, sorted.
+    pub fn keywords(&self) -> &[K] {
+        &self.keywords
+    }
+
+    /// Distances to all indexed keywords, in the order of [`keywords`](Self::keywords).
+    pub fn distances(&self) -> &DistanceMap<N, D> {
+        &self.dists
+    }
+
+    /// Distances to `keywords`, in their order. Fails with [`SkylineError::KeywordNotIndexed`] for a keyword
+    /// outside the index.
+    pub fn select(&self, keywords: &[K]) -> Result<DistanceMap<N, D>, SkylineError> {
+        let columns = keywords
+            .iter()
+            .enumerate()
+            .map(|(keyword_idx, keyword)| {
+                self.keywords
+                    .binary_search(keyword)
+                    .map_err(|_| SkylineError::KeywordNotIndexed { keyword_idx })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self
+            .dists
+            .iter()
+            .map(|(&node, dist)| (node, columns.iter().map(|&column| dist[column]).collect()))
+            .collect())
+    }
+
+    /// Gives `keyword` to `node` of `graph`, the graph the index was built from, and returns whether `node` did not
+    /// have it. Only the column of `keyword` is updated, by a BFS from `node` that stops at nodes it does not
+    /// improve; a new keyword gets a new column. Returns `false` and leaves the index unchanged if `node` is not in
+    /// the index.
+    pub fn add_keyword<G>(&mut self, graph: G, node: N, keyword: K) -> bool
+    where
+        G: IntoNeighborsDirected<NodeId = N>,
+    {
+        if !self.dists.contains_key(&node) {
+            return false;
+        }
+        let column = match self.keywords.binary_search(&keyword) {
+            Ok(column) => column,
+            Err(column) => {
+                self.keywords.insert(column, keyword);
+                for dist in self.dists.values_mut() {
+                    dist.insert(column, D::max_value());
+                }
+                column
+            }
+        };
+        if self.dists[&node][column] == D::zero() {
+            return false;
+        }
+        multi_source_bfs(
+            graph,
+            &mut self.dists,
+            column,
+            [node],
+            D::max_value(),
+            &[Direction::Incoming],
+        );
+        true
+    }
+
+    /// Takes `keyword` from `node` of `graph`, the graph the index was built from, and returns whether `node` had
+    /// it. Only the column of `keyword` is searched again, from its remaining nodes; the column is dropped when no
+    /// node has `keyword` anymore. Returns `false` if `node` is not in the index.
+    pub fn remove_keyword<G>(&mut self, graph: G, node: N, keyword: &K) -> bool
+    where
+        G: IntoNeighborsDirected<NodeId = N>,
+    {
+        let Ok(column) = self.keywords.binary_search(keyword) else {
+            return false;
+        };
+        match self.dists.get(&node) {
+            Some(dist) if dist[column] == D::zero() => (),
+            _ => return false,
+        }
+        // the nodes at distance zero are exactly the nodes with the keyword
+        let sources: Vec<_> = self
+            .dists
+            .iter()
+            .filter(|&(&other, dist)| other != node && dist[column] == D::zero())
+            .map(|(&other, _)| other)
+            .collect();
+        if sources.is_empty() {
+            self.keywords.remove(column);
+            for dist in self.dists.values_mut() {
+                dist.remove(column);
+            }
+            return true;
+        }
+        for dist in self.dists.values_mut() {
+            dist[column] = D::max_value();
+        }
+        multi_source_bfs(
+            graph,
+            &mut self.dists,
+            column,
+            sources,
+            D::max_value(),
+            &[Direction::Incoming],
+        );
+        true
+    }
+
+    /// Same result as [`semantic_place_skyline`](crate::semantic_place_skyline) for `keywords`, without searching
+    /// the graph.
+    pub fn query(&self, keywords: &[K]) -> Result<Skyline<N, D>, SkylineError> {
+        if keywords.is_empty() {
+            return Err(SkylineError::EmptyKeywords);
+        }
+        Ok(minimal_elements(&self.select(keywords)?))
+    }
+}
+
+/// Writes `index` as text to `out`, so that it can be read back by [`load_index`] instead of being computed again.
+///
+/// The first line is `keywords: ` followed by the comma-separated keywords, then each node follows in ascending
+/// order on a `node: distances` line. Keywords should not display with commas, or they cannot be read back.
+pub fn save_index<N, K, D, W>(index: &KeywordDistanceIndex<N, K, D>, mut out: W) -> io::Result<()>
+where
+    N: Copy + Ord + Display,
+    K: Display,
+    D: Display,
+    W: Write,
+{
+    writeln!(out, "keywords: {}", join(&index.keywords))?;
+    let mut nodes: Vec<_> = index.dists.iter().collect();
+    nodes.sort_unstable_by_key(|&(&node, _)| node);
+    for (node, dist) in nodes {
+        writeln!(out, "{}: {}", node, join(dist))?;
+    }
+    out.flush()
+}
+
+/// Reads an index written by [`save_index`]. Fails with [`io::ErrorKind::InvalidData`] on malformed input, including
+/// a node listed twice.
+pub fn load_index<N, K, D, R>(input: R) -> io::Result<KeywordDistanceIndex<N, K, D>>
+where
+    N: Hash + Eq + FromStr,
+    K: Ord + FromStr,
+    D: FromStr,
+    R: BufRead,
+{
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut lines = input.lines();
+    let header = lines.next().transpose()?.unwrap_or_default();
+    let keywords: Vec<K> = header
+        .strip_prefix("keywords:")
+        .and_then(|keywords| split(keywords).ok())
+        .ok_or_else(|| invalid("expect 'keywords:' header at line 1".to_owned()))?;
+    if keywords.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(invalid(
+            "expect sorted and distinct keywords at line 1".to_owned(),
+        ));
+    }
+    let mut dists = DistanceMap::default();
+    for (line_idx, line) in lines.enumerate() {
+        let line = line?;
+        let line_number = line_idx + 2;
+        let (node, dist) = line
+            .split_once(':')
+            .ok_or_else(|| invalid(format!("expect ':' at line {}", line_number)))?;
+        let node = node
+            .trim()
+            .parse()
+            .map_err(|_| invalid(format!("invalid node at line {}", line_number)))?;
+        let dist: Vec<D> = split(dist)
+            .map_err(|_| invalid(format!("invalid distance at line {}", line_number)))?;
+        if dist.len() != keywords.len() {
+            return Err(invalid(format!(
+                "expect {} distances at line {}",
+                keywords.len(),
+                line_number
+            )));
+        }
+        if dists.insert(node, dist).is_some() {
+            return Err(invalid(format!("duplicate node at line {}", line_number)));
+        }
+    }
+    Ok(KeywordDistanceIndex { keywords, dists })
+}
+
+fn join<T: Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(T::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parses comma-separated values, where an empty list is written as nothing.
+fn split<T: FromStr>(values: &str) -> Result<Vec<T>, T::Err> {
+    let values = values.trim();
+    if values.is_empty() {
+        return Ok(Vec::new());
+    }
+    values
+        .split(',')
+        .map(|value| value.trim().parse())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{random_graph, Graph, XorShift};
+
+    #[test]
+    fn keyword_updates_match_rebuild() {
+        let mut rng = XorShift::new(265);
+        for _ in 0..10 {
+            let (graph, mut node_to_keyword) = random_graph(&mut rng, 40, 90, 4);
+            let mut index: KeywordDistanceIndex<u32, u32, u32> =
+                KeywordDistanceIndex::new(&graph, &node_to_keyword);
+            for _ in 0..30 {
+                let node = rng.below(40);
+                let keyword = rng.below(4);
+                let node_keywords = node_to_keyword.entry(node).or_default();
+                match node_keywords.binary_search(&keyword) {
+                    Ok(idx) => {
+                        node_keywords.remove(idx);
+                        assert!(index.remove_keyword(&graph, node, &keyword));
+                    }
+                    Err(idx) => {
+                        node_keywords.insert(idx, keyword);
+                        assert!(index.add_keyword(&graph, node, keyword));
+                    }
+                }
+                let rebuilt = KeywordDistanceIndex::new(&graph, &node_to_keyword);
+                assert_eq!(index.keywords(), rebuilt.keywords());
+                assert_eq!(index.distances(), rebuilt.distances());
+            }
+        }
+    }
+
+    #[test]
+    fn keyword_updates_ignore_unknown_nodes() {
+        let graph = Graph::from_edges([(0, 1)]);
+        let node_to_keyword = HashMap::from([(1, vec![7])]);
+        let mut index: KeywordDistanceIndex<u32, u32, u32> =
+            KeywordDistanceIndex::new(&graph, &node_to_keyword);
+        assert!(!index.add_keyword(&graph, 5, 8));
+        assert!(!index.remove_keyword(&graph, 5, &7));
+        assert_eq!(index.keywords(), [7]);
+        assert_eq!(index.distances().len(), 2);
+    }
+
+    #[test]
+    fn saved_index_loads_back() {
+        let mut rng = XorShift::new(263);
+        let (graph, node_to_keyword) = random_graph(&mut rng, 40, 70, 4);
+        let index: KeywordDistanceIndex<u32, u32, u32> =
+            KeywordDistanceIndex::new(&graph, &node_to_keyword);
+        let mut saved = Vec::new();
+        save_index(&index, &mut saved).unwrap();
+        let loaded: KeywordDistanceIndex<u32, u32, u32> = load_index(saved.as_slice()).unwrap();
+        assert_eq!(loaded.keywords(), index.keywords());
+        assert_eq!(loaded.distances(), index.distances());
+    }
+
+    #[test]
+    fn malformed_index_is_invalid_data() {
+        let load = |input: &str| {
+            load_index::<u32, u32, u32, _>(input.as_bytes())
+                .map(|_| ())
+                .map_err(|err| (err.kind(), err.to_string()))
+        };
+        let invalid = |message: &str| Err((io::ErrorKind::InvalidData, message.to_owned()));
+        assert_eq!(load("keywords: 1,2\n0: 0,1\n"), Ok(()));
+        assert_eq!(
+            load("nodes: 1,2\n0: 0,1\n"),
+            invalid("expect 'keywords:' header at line 1")
+        );
+        assert_eq!(
+            load("keywords: 1,2\n0: 0,1\n1: 0\n"),
+            invalid("expect 2 distances at line 3")
+        );
+        assert_eq!(
+            load("keywords: 2,1\n0: 0,1\n"),
+            invalid("expect sorted and distinct keywords at line 1")
+        );
+        assert_eq!(
+            load("keywords: 1,2\n0: 0,1\n0: 1,0\n"),
+            invalid("duplicate node at line 3")
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Mapping between textual keywords and the integer keywords used by the queries.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Assigns consecutive `u32` ids to keyword strings, starting at zero, so that datasets with textual keywords can
+/// be queried without encoding them first.
+#[derive(Clone, Debug, Default)]
+pub struct KeywordInterner {
+    ids: HashMap<String, u32>,
+    names: Vec<String>,
+}
+
+impl KeywordInterner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Id of `name`, assigning the next one if `name` is new.
+    pub fn intern(&mut self, name: &str) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = u32::try_from(self.names.len()).expect("more than u32::MAX keywords");
+        self.ids.insert(name.to_owned(), id);
+        self.names.push(name.to_owned());
+        id
+    }
+
+    /// Id of `name`, if it has been interned.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    /// Keyword string of `id`, if it has been assigned.
+    pub fn name(&self, id: u32) -> Option<&str> {
+        self.names.get(id as usize).map(String::as_str)
+    }
+
+    /// Number of interned keywords.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether no keyword has been interned.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Interns the keywords of every node, returning sorted id lists as expected by the query functions.
+    pub fn intern_node_keywords<N, S>(
+        &mut self,
+        node_to_keyword: HashMap<N, Vec<S>>,
+    ) -> HashMap<N, Vec<u32>>
+    where
+        N: Hash + Eq,
+        S: AsRef<str>,
+    {
+        node_to_keyword
+            .into_iter()
+            .map(|(node, names)| {
+                let mut keywords: Vec<_> = names
+                    .iter()
+                    .map(|name| self.intern(name.as_ref()))
+                    .collect();
+                keywords.sort_unstable();
+                keywords.dedup();
+                (node, keywords)
+            })
+            .collect()
+    }
+}
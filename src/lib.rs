@@ -1,71 +1,3001 @@
 #![feature(is_some_and)]
 
-use std::cmp::Ordering;
-use std::collections::{HashMap, VecDeque};
-use std::hash::Hash;
+mod csr;
+mod engine;
+#[cfg(feature = "cffi")]
+pub mod ffi;
+mod flat;
+pub mod fxhash;
+mod incremental;
+mod index;
+mod interner;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod travel_time;
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::hash_map::Entry;
+use std::collections::hash_map::RandomState;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 
 use num_traits::bounds::UpperBounded;
-use num_traits::{One, Zero};
-use petgraph::visit::{IntoNeighborsDirected, IntoNodeIdentifiers};
+use num_traits::{FromPrimitive, One, SaturatingAdd, ToPrimitive, Zero};
+use petgraph::graphmap::{DiGraphMap, NodeTrait};
+use petgraph::visit::{EdgeRef, IntoEdgesDirected, IntoNeighborsDirected, IntoNodeIdentifiers};
 use petgraph::Direction;
 
-/// Main query function. Each entry of `node_to_keyword` should be sorted.
-pub fn semantic_place_skyline<G, K, D>(
+pub use crate::csr::{semantic_place_skyline_csr, CsrGraph};
+pub use crate::engine::{SkylineEngine, Update};
+pub use crate::flat::{
+    keyword_distances_flat, semantic_place_skyline_flat, skyline_flat, FlatDistances,
+};
+pub use crate::incremental::{
+    on_edge_added, on_edge_removed, on_keyword_added, on_keyword_removed, on_node_added,
+    on_node_removed,
+};
+pub use crate::index::{load_index, save_index, KeywordDistanceIndex};
+pub use crate::interner::KeywordInterner;
+#[cfg(feature = "parallel")]
+pub use crate::parallel::{
+    keyword_distances_parallel, semantic_place_skyline_parallel, skyline_parallel,
+};
+pub use crate::travel_time::TravelTime;
+
+/// Distances from each node to each queried keyword, as computed by [`keyword_distances`].
+pub type DistanceMap<N, D, S = RandomState> = HashMap<N, Vec<D>, S>;
+
+/// Hasher of the maps a keyword search works on before handing out a [`DistanceMap`]: SipHash by default,
+/// [`fxhash::FxHasher`] with the `fast-hash` feature.
+#[cfg(not(feature = "fast-hash"))]
+type SearchHasher = RandomState;
+#[cfg(feature = "fast-hash")]
+type SearchHasher = fxhash::FxBuildHasher;
+
+/// Hands out the distances of a search as a [`DistanceMap`], moving them into a SipHash map if the `fast-hash`
+/// feature changed the hasher of the search. Rehashing once is cheap next to the lookups of the search itself.
+fn into_distance_map<N: Hash + Eq, D>(dists: DistanceMap<N, D, SearchHasher>) -> DistanceMap<N, D> {
+    #[cfg(feature = "fast-hash")]
+    return dists.into_iter().collect();
+    #[cfg(not(feature = "fast-hash"))]
+    return dists;
+}
+
+/// Skyline nodes with their distance vectors, as returned by the query functions.
+pub type Skyline<N, D> = Vec<(N, Vec<D>)>;
+
+/// A node with its distance vector, see [`best_skyline_place`].
+pub type Place<N, D> = (N, Vec<D>);
+
+/// Skyline nodes with their distance vectors and keyword counts, see [`semantic_place_skyline_with_density`].
+pub type DensitySkyline<N, D> = Vec<(N, Vec<D>, Vec<usize>)>;
+
+/// Ranked nodes with their distance vectors, aggregates and skyline flags, see [`ranked_with_skyline_flag`].
+pub type FlaggedRanking<N, D> = Vec<(N, Vec<D>, f64, bool)>;
+
+/// Nodes with their distance vectors and scores, see [`semantic_place_top_k`] and [`top_k_by_aggregate`].
+pub type ScoredPlaces<N, D> = Vec<(N, Vec<D>, f64)>;
+
+/// Main query function. Each entry of `node_to_keyword` should be sorted. A graph without nodes yields an empty
+/// skyline.
+///
+/// # Panics
+///
+/// Panics if `keywords` is empty. Unlike the other query functions it does not return [`SkylineError`], see
+/// [`try_semantic_place_skyline`] for the fallible form.
+pub fn semantic_place_skyline<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> Vec<(G::NodeId, Vec<D>)>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    semantic_place_skyline_with_progress(graph, node_to_keyword, keywords, |_| {})
+        .unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Like [`semantic_place_skyline`], but checks the query with [`validate_query`] first instead of panicking or
+/// silently reporting unreachable keywords.
+pub fn try_semantic_place_skyline<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    validate_query(graph, node_to_keyword, keywords)?;
+    Ok(semantic_place_skyline(graph, node_to_keyword, keywords))
+}
+
+/// Checks that `keywords` is not empty, that every node of `node_to_keyword` is part of `graph` and that every
+/// keyword occurs in some node. A keyword node is at distance zero of itself, so such a keyword is reachable from
+/// some node; see [`validate_distances`] for distances restricted to candidates.
+pub fn validate_query<G, K>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> Result<(), SkylineError>
+where
+    G: IntoNodeIdentifiers,
+    G::NodeId: Hash + Eq,
+    K: Ord,
+{
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let nodes: HashSet<_> = graph.node_identifiers().collect();
+    let count = node_to_keyword
+        .keys()
+        .filter(|node| !nodes.contains(node))
+        .count();
+    if count > 0 {
+        return Err(SkylineError::UnknownNodes { count });
+    }
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        let found = node_to_keyword
+            .values()
+            .any(|node_keywords| node_keywords.binary_search(keyword).is_ok());
+        if !found {
+            return Err(SkylineError::KeywordNotFound { keyword_idx });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every keyword column of `dists` is reachable from some node, i.e. not `D::max_value()` for every
+/// node, e.g. after restricting the distances to candidates. Run it before the skyline filter to tell an
+/// unreachable keyword apart from an empty skyline. No node at all is no failure.
+pub fn validate_distances<N, D>(dists: &DistanceMap<N, D>) -> Result<(), SkylineError>
+where
+    D: Copy + Eq + UpperBounded,
+{
+    let Some(num_keywords) = dists.values().next().map(Vec::len) else {
+        return Ok(());
+    };
+    match (0..num_keywords)
+        .find(|&keyword_idx| dists.values().all(|du| du[keyword_idx] == D::max_value()))
+    {
+        Some(keyword_idx) => Err(SkylineError::UnreachableKeyword { keyword_idx }),
+        None => Ok(()),
+    }
+}
+
+/// Progress of a query, reported by [`semantic_place_skyline_with_progress`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Progress {
+    /// The search for keyword `keyword_idx` of `num_keywords` is starting.
+    Keyword {
+        keyword_idx: usize,
+        num_keywords: usize,
+    },
+    /// The skyline has been computed and holds `size` nodes.
+    Skyline { size: usize },
+}
+
+/// Like [`semantic_place_skyline`], but reports its [`Progress`] to `on_progress`.
+pub fn semantic_place_skyline_with_progress<G, K, D, P>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    mut on_progress: P,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+    P: FnMut(Progress),
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    // nodes containing every keyword dominate all others, so the search can be skipped
+    let dominators: Vec<_> = graph
+        .node_identifiers()
+        .filter(|node| {
+            node_to_keyword.get(node).is_some_and(|node_keywords| {
+                keywords
+                    .iter()
+                    .all(|keyword| node_keywords.binary_search(keyword).is_ok())
+            })
+        })
+        .map(|node| (node, vec![D::zero(); keywords.len()]))
+        .collect();
+    if !dominators.is_empty() {
+        on_progress(Progress::Skyline {
+            size: dominators.len(),
+        });
+        return Ok(dominators);
+    }
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); keywords.len()]))
+        .collect();
+    let keywords_of = keywords_in(node_to_keyword);
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        on_progress(Progress::Keyword {
+            keyword_idx,
+            num_keywords: keywords.len(),
+        });
+        let sources = graph
+            .node_identifiers()
+            .filter(|&node| keywords_of(node).binary_search(keyword).is_ok());
+        multi_source_bfs(
+            graph,
+            &mut dists,
+            keyword_idx,
+            sources,
+            D::max_value(),
+            &[Direction::Incoming],
+        );
+    }
+    let skyline = minimal_elements(&dists);
+    on_progress(Progress::Skyline {
+        size: skyline.len(),
+    });
+    Ok(skyline)
+}
+
+/// Like [`semantic_place_skyline`], but refuses to run if the [`estimated_work`] of the query exceeds `max_work`.
+/// `None` disables the check.
+pub fn semantic_place_skyline_with_budget<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    max_work: Option<u64>,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    if let Some(budget) = max_work {
+        let estimated = estimated_work(graph.node_identifiers().count(), keywords.len());
+        if estimated > budget {
+            return Err(SkylineError::BudgetExceeded { estimated, budget });
+        }
+    }
+    Ok(semantic_place_skyline(graph, node_to_keyword, keywords))
+}
+
+/// Inverts `node_to_keyword` into the nodes holding each keyword, for [`semantic_place_skyline_with_index`]. The
+/// nodes of each keyword are sorted.
+pub fn build_keyword_index<N, K>(node_to_keyword: &HashMap<N, Vec<K>>) -> HashMap<K, Vec<N>>
+where
+    N: Copy + Ord,
+    K: Clone + Hash + Eq,
+{
+    let mut index: HashMap<K, Vec<N>> = HashMap::new();
+    for (&node, node_keywords) in node_to_keyword {
+        for keyword in node_keywords {
+            index.entry(keyword.clone()).or_default().push(node);
+        }
+    }
+    for nodes in index.values_mut() {
+        nodes.sort_unstable();
+    }
+    index
+}
+
+/// Like [`semantic_place_skyline`], but takes the keyword nodes from a [`build_keyword_index`] index, so that
+/// seeding each search costs O(sources) instead of a scan over all nodes. Reuse the index across queries.
+pub fn semantic_place_skyline_with_index<G, K, D>(
+    graph: G,
+    keyword_index: &HashMap<K, Vec<G::NodeId>>,
+    keywords: &[K],
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Hash + Eq,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); keywords.len()]))
+        .collect();
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        // keyword nodes outside the graph are ignored
+        let sources: Vec<_> = keyword_index
+            .get(keyword)
+            .into_iter()
+            .flatten()
+            .filter(|node| dists.contains_key(node))
+            .copied()
+            .collect();
+        multi_source_bfs(
+            graph,
+            &mut dists,
+            keyword_idx,
+            sources,
+            D::max_value(),
+            &[Direction::Incoming],
+        );
+    }
+    Ok(minimal_elements(&dists))
+}
+
+/// Skyline of a query that may have been cancelled, see [`semantic_place_skyline_cancellable`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialSkyline<N, D> {
+    /// Skyline members with their distance vectors.
+    pub skyline: Skyline<N, D>,
+    /// Whether all distances were computed. If not, the skyline is only approximate: distances not computed yet
+    /// are reported as `D::max_value()`.
+    pub complete: bool,
+}
+
+/// Like [`semantic_place_skyline`], but stops the search as soon as `cancel` is set, e.g. from another thread.
+///
+/// A cancelled query fails with [`SkylineError::Cancelled`], unless `partial_on_cancel` is set: then the skyline of
+/// the distances gathered so far is returned, flagged as incomplete.
+pub fn semantic_place_skyline_cancellable<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    cancel: &AtomicBool,
+    partial_on_cancel: bool,
+) -> Result<PartialSkyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); keywords.len()]))
+        .collect();
+    let keywords_of = keywords_in(node_to_keyword);
+    let mut complete = true;
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        if cancel.load(AtomicOrdering::Relaxed) {
+            complete = false;
+            break;
+        }
+        let queue = graph
+            .node_identifiers()
+            .filter(|&node| keywords_of(node).binary_search(keyword).is_ok())
+            .map(|node| {
+                dists.get_mut(&node).unwrap()[keyword_idx] = D::zero();
+                (node, D::zero(), ())
+            })
+            .collect();
+        complete = bfs_from(
+            queue,
+            D::max_value(),
+            |current| graph.neighbors_directed(current, Direction::Incoming),
+            |_, _, nbr, dist| {
+                if cancel.load(AtomicOrdering::Relaxed) {
+                    return Relax::Stop;
+                }
+                relax_min(&mut dists.get_mut(&nbr).unwrap()[keyword_idx], dist)
+            },
+        );
+        if !complete {
+            break;
+        }
+    }
+    if !complete && !partial_on_cancel {
+        return Err(SkylineError::Cancelled);
+    }
+    Ok(PartialSkyline {
+        skyline: minimal_elements(&dists),
+        complete,
+    })
+}
+
+/// `f64` ordered by [`f64::total_cmp`], for skylines over float distances without NaN.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TotalF64(f64);
+
+impl Eq for TotalF64 {}
+
+impl PartialOrd for TotalF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TotalF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Like [`semantic_place_skyline`], but nodes hold keywords with a confidence in `[0, 1]`, and each entry of
+/// `node_to_keyword` should be sorted by keyword. A node holding a keyword with confidence `c` starts the search at
+/// distance `penalty * (1 - c)` instead of zero, so a fully confident node is at distance zero and less confident
+/// ones count as up to `penalty` hops farther. Unreachable keywords are reported as `f64::INFINITY`.
+///
+/// # Panics
+///
+/// Panics if `penalty` is negative or not finite, or if a confidence lies outside `[0, 1]`.
+pub fn semantic_place_skyline_soft<G, K>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<(K, f64)>>,
+    keywords: &[K],
+    penalty: f64,
+) -> Result<Skyline<G::NodeId, f64>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    // the penalty should be a finite, non-negative distance
+    assert!(penalty.is_finite() && penalty >= 0.0);
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![TotalF64(f64::INFINITY); keywords.len()]))
+        .collect();
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        let sources = graph.node_identifiers().filter_map(|node| {
+            let node_keywords = node_to_keyword.get(&node)?;
+            let idx = node_keywords
+                .binary_search_by(|(k, _)| k.cmp(keyword))
+                .ok()?;
+            let confidence = node_keywords[idx].1;
+            // confidences should lie in [0, 1]
+            assert!((0.0..=1.0).contains(&confidence));
+            Some((node, TotalF64(penalty * (1.0 - confidence))))
+        });
+        // sources start at different distances, so nodes are settled in order of distance
+        multi_source_dijkstra(&mut dists, keyword_idx, sources, |current, dist| {
+            graph
+                .neighbors_directed(current, Direction::Incoming)
+                .map(move |nbr| (nbr, TotalF64(dist.0 + 1.0)))
+        });
+    }
+    Ok(minimal_elements(&dists)
+        .into_iter()
+        .map(|(node, dist)| (node, dist.into_iter().map(|d| d.0).collect()))
+        .collect())
+}
+
+/// Like [`semantic_place_skyline`], but column `i` is seeded by all nodes having a keyword accepted by `matchers[i]`
+/// instead of a single exact keyword, e.g. every keyword with a given prefix.
+pub fn semantic_place_skyline_by<G, K, M, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    matchers: &[M],
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    M: Fn(&K) -> bool,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one matcher should be provided
+    if matchers.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let dists = keyword_distances_by(
+        graph,
+        keywords_in(node_to_keyword),
+        matchers.len(),
+        |keyword_idx, node_keywords| node_keywords.iter().any(&matchers[keyword_idx]),
+        None,
+        &[Direction::Incoming],
+    );
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`keyword_distances`], but the search stops at `max_distance`: keywords farther away are reported as
+/// unreachable, i.e. `D::max_value()`, and the nodes beyond the radius are never expanded.
+pub fn keyword_distances_within<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    max_distance: D,
+) -> DistanceMap<G::NodeId, D>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    keyword_distances_by(
+        graph,
+        keywords_in(node_to_keyword),
+        keywords.len(),
+        |keyword_idx, node_keywords| node_keywords.binary_search(&keywords[keyword_idx]).is_ok(),
+        Some(&vec![max_distance; keywords.len()]),
+        &[Direction::Incoming],
+    )
+}
+
+/// Like [`semantic_place_skyline`], but only keywords within `max_distance` of a node count as reachable, see
+/// [`keyword_distances_within`].
+pub fn semantic_place_skyline_within<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    max_distance: D,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    Ok(minimal_elements(&keyword_distances_within(
+        graph,
+        node_to_keyword,
+        keywords,
+        max_distance,
+    )))
+}
+
+/// Like [`semantic_place_skyline`], but keyword `keywords[i]` is only searched up to distance `max_dist[i]`; nodes
+/// farther away keep `D::max_value()` in that column.
+///
+/// # Panics
+///
+/// Panics if `max_dist` does not hold one cutoff per keyword.
+pub fn semantic_place_skyline_bounded<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    max_dist: &[D],
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    // one cutoff per keyword
+    assert_eq!(keywords.len(), max_dist.len());
+    let dists = keyword_distances_by(
+        graph,
+        keywords_in(node_to_keyword),
+        keywords.len(),
+        |keyword_idx, node_keywords| node_keywords.binary_search(&keywords[keyword_idx]).is_ok(),
+        Some(max_dist),
+        &[Direction::Incoming],
+    );
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`semantic_place_skyline_bounded`], but only the nodes within `max_dist[i]` of `keywords[i]` for every `i`
+/// are candidates, e.g. a cafe within 3 hops and a pharmacy within 5. The others are dropped before the dominance
+/// checks, so tight bounds also shrink the O(n²) skyline filter.
+///
+/// Tight bounds can leave no candidate. The skyline is then empty, unless `fallback_nearest` is set: then the node
+/// with the smallest sum of distances is returned instead, flagged as a fallback, see [`FallbackSkyline`].
+///
+/// # Panics
+///
+/// Panics if `max_dist` does not hold one bound per keyword.
+pub fn semantic_place_skyline_constrained<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    max_dist: &[D],
+    fallback_nearest: bool,
+) -> Result<FallbackSkyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded + ToPrimitive,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    // one bound per keyword
+    assert_eq!(keywords.len(), max_dist.len());
+    let mut dists = keyword_distances_by(
+        graph,
+        keywords_in(node_to_keyword),
+        keywords.len(),
+        |keyword_idx, node_keywords| node_keywords.binary_search(&keywords[keyword_idx]).is_ok(),
+        Some(max_dist),
+        &[Direction::Incoming],
+    );
+    dists.retain(|_, du| du.iter().zip(max_dist).all(|(d, max_d)| d <= max_d));
+    if fallback_nearest && dists.is_empty() {
+        // the bounded search left the distances beyond the bounds unknown
+        return Ok(nearest_fallback(&keyword_distances(
+            graph,
+            node_to_keyword,
+            keywords,
+        )));
+    }
+    Ok(FallbackSkyline {
+        skyline: minimal_elements(&dists),
+        fallback: false,
+    })
+}
+
+/// Skyline of a query that may have fallen back to the nearest node, see [`semantic_place_skyline_constrained`] and
+/// [`semantic_place_skyline_with_anchor`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FallbackSkyline<N, D> {
+    /// Skyline members with their distance vectors.
+    pub skyline: Skyline<N, D>,
+    /// Whether no node was a candidate, so `skyline` holds the node with the smallest sum of distances instead, with
+    /// unreachable keywords counting as infinitely far. Only set if the query asked for `fallback_nearest`.
+    pub fallback: bool,
+}
+
+/// Falls back to the node of `dists` with the smallest sum of distances, see [`FallbackSkyline`]. Sums over an
+/// unreachable keyword are infinite; they rank by the number of unreachable keywords, then by the sum of the
+/// others, and ties go to the smallest node. A graph without nodes has no fallback.
+fn nearest_fallback<N, D>(dists: &DistanceMap<N, D>) -> FallbackSkyline<N, D>
+where
+    N: Copy + Ord,
+    D: Copy + Ord + UpperBounded + ToPrimitive,
+{
+    let nearest = dists
+        .iter()
+        .map(|(&node, du)| {
+            let reachable = du.iter().filter(|&&d| d != D::max_value());
+            let num_unreachable = du.len() - reachable.clone().count();
+            let sum: f64 = reachable.map(|d| d.to_f64().unwrap_or(f64::INFINITY)).sum();
+            ((num_unreachable, TotalF64(sum), node), du)
+        })
+        .min_by(|(key1, _), (key2, _)| key1.cmp(key2));
+    FallbackSkyline {
+        fallback: nearest.is_some(),
+        skyline: nearest
+            .map(|((_, _, node), du)| (node, du.clone()))
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Like [`semantic_place_skyline_bounded`], but column `i` holds the distance to the `counts[i]`-th nearest node
+/// containing `keywords[i]`, e.g. to require three pharmacies nearby. Nodes with fewer than `counts[i]` such nodes
+/// within `max_dist[i]` keep `D::max_value()` in that column.
+///
+/// # Panics
+///
+/// Panics if `counts` or `max_dist` does not hold one entry per keyword, or if a count is zero.
+pub fn semantic_place_skyline_with_multiplicity<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    counts: &[usize],
+    max_dist: &[D],
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    // one count and one cutoff per keyword
+    assert_eq!(keywords.len(), counts.len());
+    assert_eq!(keywords.len(), max_dist.len());
+    // at least one occurrence should be required
+    assert!(counts.iter().all(|&count| count > 0));
+    let keywords_of = keywords_in(node_to_keyword);
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); keywords.len()]))
+        .collect();
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        let count = counts[keyword_idx];
+        // the distinct keyword nodes found so far from each node, nearest first
+        let mut found: HashMap<_, Vec<_>> = HashMap::new();
+        let queue = graph
+            .node_identifiers()
+            .filter(|&node| keywords_of(node).binary_search(keyword).is_ok())
+            .map(|node| {
+                found.insert(node, vec![node]);
+                if count == 1 {
+                    dists.get_mut(&node).unwrap()[keyword_idx] = D::zero();
+                }
+                (node, D::zero(), node)
+            })
+            .collect();
+        // keyword nodes are found in order of distance, so the `count`-th one found is the `count`-th nearest. A
+        // keyword node only spreads through nodes where it is among the `count` nearest, since any node it could
+        // reach from there already has `count` keyword nodes at most as far.
+        bfs_from(
+            queue,
+            max_dist[keyword_idx],
+            |current| graph.neighbors_directed(current, Direction::Incoming),
+            |_, source, nbr, dist| {
+                let nbr_found = found.entry(nbr).or_default();
+                if nbr_found.len() == count || nbr_found.contains(&source) {
+                    return Relax::Skip;
+                }
+                nbr_found.push(source);
+                if nbr_found.len() == count {
+                    dists.get_mut(&nbr).unwrap()[keyword_idx] = dist;
+                }
+                Relax::Push
+            },
+        );
+    }
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`semantic_place_skyline`], but traversing edge `u -> v` costs `hop_weight_fn(u, v)` hops instead of one.
+///
+/// Distances are computed by Dial's algorithm, a bucket queue indexed by distance, which suits small integer
+/// weights better than a binary heap. Zero weights are allowed, and distances beyond `D::max_value()` saturate to it.
+pub fn semantic_place_skyline_hop_weighted<G, K, D, W>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    hop_weight_fn: W,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + UpperBounded + ToPrimitive + FromPrimitive,
+    W: Fn(G::NodeId, G::NodeId) -> u32,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); keywords.len()]))
+        .collect();
+    let keywords_of = keywords_in(node_to_keyword);
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        let sources = graph
+            .node_identifiers()
+            .filter(|&node| keywords_of(node).binary_search(keyword).is_ok());
+        multi_source_dial(
+            graph,
+            &mut dists,
+            keyword_idx,
+            sources,
+            &[Direction::Incoming],
+            &|source, target, _| hop_weight_fn(source, target),
+        );
+    }
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`semantic_place_skyline`], but traversing an edge costs `edge_cost(edge)` instead of one hop, e.g. the travel
+/// time stored as its weight. Costs should be non-negative. Distances are computed by a multi-source Dijkstra search
+/// per keyword, and sums of costs saturate at `D::max_value()`.
+pub fn semantic_place_skyline_weighted<G, K, D, F>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    edge_cost: F,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoEdgesDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + SaturatingAdd + UpperBounded,
+    F: Fn(G::EdgeRef) -> D,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); keywords.len()]))
+        .collect();
+    let edge_cost = &edge_cost;
+    let keywords_of = keywords_in(node_to_keyword);
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        let sources = graph
+            .node_identifiers()
+            .filter(|&node| keywords_of(node).binary_search(keyword).is_ok())
+            .map(|node| (node, D::zero()));
+        multi_source_dijkstra(&mut dists, keyword_idx, sources, |current, dist| {
+            graph
+                .edges_directed(current, Direction::Incoming)
+                .map(move |edge| (edge.source(), dist.saturating_add(&edge_cost(edge))))
+        });
+    }
+    Ok(minimal_elements(&dists))
+}
+
+/// Cost multipliers of traversing an edge along and against its direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraversalCost<D> {
+    /// Multiplier of edges traversed from source to target.
+    pub forward: D,
+    /// Multiplier of edges traversed from target to source.
+    pub reverse: D,
+}
+
+/// Like [`semantic_place_skyline_hop_weighted`], but edges can also be traversed against their direction: going
+/// along edge `u -> v` costs `hop_weight_fn(u, v) * cost.forward` hops, going from `v` back to `u` costs
+/// `hop_weight_fn(u, v) * cost.reverse`, e.g. to let wrong-way driving cost double. Products saturate at
+/// `u32::MAX` hops.
+///
+/// Fails with [`SkylineError::CostOutOfRange`] if a multiplier is not a hop count, i.e. does not convert to `u32`.
+pub fn semantic_place_skyline_with_traversal_cost<G, K, D, W>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    hop_weight_fn: W,
+    cost: TraversalCost<D>,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + UpperBounded + ToPrimitive + FromPrimitive,
+    W: Fn(G::NodeId, G::NodeId) -> u32,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let (Some(forward), Some(reverse)) = (cost.forward.to_u32(), cost.reverse.to_u32()) else {
+        return Err(SkylineError::CostOutOfRange);
+    };
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); keywords.len()]))
+        .collect();
+    let keywords_of = keywords_in(node_to_keyword);
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        let sources = graph
+            .node_identifiers()
+            .filter(|&node| keywords_of(node).binary_search(keyword).is_ok());
+        // searching from the sources, incoming edges are traversed forward by the reached node
+        multi_source_dial(
+            graph,
+            &mut dists,
+            keyword_idx,
+            sources,
+            &[Direction::Incoming, Direction::Outgoing],
+            &|source, target, direction| {
+                let multiplier = match direction {
+                    Direction::Incoming => forward,
+                    Direction::Outgoing => reverse,
+                };
+                hop_weight_fn(source, target).saturating_mul(multiplier)
+            },
+        );
+    }
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`multi_source_bfs`], but expanding to the neighbors in each of `directions` with integer edge weights:
+/// reaching a neighbor through edge `u -> v` found in `direction` costs `weight(u, v, direction)`. Distances that
+/// `D` cannot represent below `D::max_value()` saturate to it, i.e. stay unreachable.
+///
+/// Only the buckets from the current distance up to the largest weight ahead are kept, so memory is bounded by the
+/// largest edge weight rather than the largest distance.
+fn multi_source_dial<G, D, W>(
+    graph: G,
+    dists: &mut DistanceMap<G::NodeId, D>,
+    keyword_idx: usize,
+    sources: impl IntoIterator<Item = G::NodeId>,
+    directions: &[Direction],
+    weight: &W,
+) where
+    G: IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    D: Copy + Ord + Zero + UpperBounded + ToPrimitive + FromPrimitive,
+    W: Fn(G::NodeId, G::NodeId, Direction) -> u32,
+{
+    // buckets[i] holds the nodes reached at distance level + i
+    let mut buckets: VecDeque<Vec<_>> = VecDeque::from([sources.into_iter().collect::<Vec<_>>()]);
+    for node in &buckets[0] {
+        *dists.get_mut(node).unwrap().get_mut(keyword_idx).unwrap() = D::zero();
+    }
+    let mut level = 0usize;
+    while !buckets.is_empty() {
+        // zero-weight edges push into the current bucket, so pop until it is drained
+        while let Some(current) = buckets[0].pop() {
+            // skip stale entries of nodes improved after they were pushed
+            if dists[&current][keyword_idx].to_usize() != Some(level) {
+                continue;
+            }
+            for &direction in directions {
+                for nbr in graph.neighbors_directed(current, direction) {
+                    let (source, target) = match direction {
+                        Direction::Incoming => (nbr, current),
+                        Direction::Outgoing => (current, nbr),
+                    };
+                    let offset = weight(source, target, direction) as usize;
+                    let next_dist = level
+                        .checked_add(offset)
+                        .and_then(D::from_usize)
+                        .unwrap_or_else(D::max_value);
+                    let nbr_dist = dists
+                        .get_mut(&nbr)
+                        .and_then(|v| v.get_mut(keyword_idx))
+                        .unwrap();
+                    // a saturated distance improves nothing
+                    if next_dist < *nbr_dist {
+                        *nbr_dist = next_dist;
+                        if buckets.len() <= offset {
+                            buckets.resize_with(offset + 1, Vec::new);
+                        }
+                        buckets[offset].push(nbr);
+                    }
+                }
+            }
+        }
+        buckets.pop_front();
+        level += 1;
+    }
+}
+
+/// Which way distances between places and keyword nodes are measured.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Length of the shortest path from the place to a keyword node.
+    #[default]
+    NodeToKeyword,
+    /// Length of the shortest path from a keyword node to the place, e.g. how far customers travel to a service.
+    KeywordToNode,
+    /// Length of the shortest path ignoring edge directions, for undirected networks stored as directed graphs.
+    /// Undirected petgraph graphs report every neighbor in both directions, so any mode works for them.
+    Undirected,
+}
+
+impl Mode {
+    /// Direction in which the search expands from the keyword nodes.
+    fn search_directions(self) -> &'static [Direction] {
+        match self {
+            Mode::NodeToKeyword => &[Direction::Incoming],
+            Mode::KeywordToNode => &[Direction::Outgoing],
+            Mode::Undirected => &[Direction::Incoming, Direction::Outgoing],
+        }
+    }
+}
+
+/// Like [`semantic_place_skyline`], but measures distances according to `mode`.
+pub fn semantic_place_skyline_with_mode<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    mode: Mode,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let dists = keyword_distances_by(
+        graph,
+        keywords_in(node_to_keyword),
+        keywords.len(),
+        |keyword_idx, node_keywords| node_keywords.binary_search(&keywords[keyword_idx]).is_ok(),
+        None,
+        mode.search_directions(),
+    );
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`semantic_place_skyline_bounded`], but also reports for each skyline member how many nodes containing
+/// `keywords[i]` lie within `max_dist[i]`.
+///
+/// Counting runs one bounded BFS per skyline member, so it costs the size of the largest radius neighborhood per
+/// member on top of the query.
+pub fn semantic_place_skyline_with_density<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    max_dist: &[D],
+) -> Result<DensitySkyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    Ok(
+        semantic_place_skyline_bounded(graph, node_to_keyword, keywords, max_dist)?
+            .into_iter()
+            .map(|(node, dist)| {
+                let counts =
+                    count_keywords_within(graph, node_to_keyword, keywords, max_dist, node);
+                (node, dist, counts)
+            })
+            .collect(),
+    )
+}
+
+/// Counts, for each keyword, the nodes containing `keywords[i]` within distance `max_dist[i]` of `node`.
+fn count_keywords_within<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    max_dist: &[D],
+    node: G::NodeId,
+) -> Vec<usize>
+where
+    G: IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    K: Ord,
+    D: Copy + Ord + Zero + One,
+{
+    let radius = max_dist.iter().copied().max().unwrap_or_else(D::zero);
+    let keywords_of = keywords_in(node_to_keyword);
+    let mut counts = vec![0; keywords.len()];
+    let mut count = |current, dist| {
+        let node_keywords = keywords_of(current);
+        for (keyword_idx, keyword) in keywords.iter().enumerate() {
+            if dist <= max_dist[keyword_idx] && node_keywords.binary_search(keyword).is_ok() {
+                counts[keyword_idx] += 1;
+            }
+        }
+    };
+    count(node, D::zero());
+    // each node is counted once, when the search first reaches it
+    let mut visited = HashSet::from([node]);
+    bfs_from(
+        VecDeque::from([(node, D::zero(), ())]),
+        radius,
+        |current| graph.neighbors_directed(current, Direction::Outgoing),
+        |_, _, nbr, dist| {
+            if !visited.insert(nbr) {
+                return Relax::Skip;
+            }
+            count(nbr, dist);
+            Relax::Push
+        },
+    );
+    counts
+}
+
+/// Like [`semantic_place_skyline`], but unreachable keywords are `unreachable` instead of `D::max_value()`: the
+/// distances start out as `unreachable` and the search treats that value as unset. Unreachable keywords still count as
+/// farther than any reachable one when computing the skyline, whatever the value of `unreachable`, which should
+/// therefore differ from every reachable distance.
+pub fn semantic_place_skyline_with_sentinel<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    unreachable: D,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let keywords_of = keywords_in(node_to_keyword);
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![unreachable; keywords.len()]))
+        .collect();
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        let queue = graph
+            .node_identifiers()
+            .filter(|&node| keywords_of(node).binary_search(keyword).is_ok())
+            .map(|node| {
+                dists.get_mut(&node).unwrap()[keyword_idx] = D::zero();
+                (node, D::zero(), ())
+            })
+            .collect();
+        bfs_from(
+            queue,
+            D::max_value(),
+            |current| graph.neighbors_directed(current, Direction::Incoming),
+            |_, _, nbr, dist| {
+                let nbr_dist = &mut dists.get_mut(&nbr).unwrap()[keyword_idx];
+                if *nbr_dist == unreachable || dist < *nbr_dist {
+                    *nbr_dist = dist;
+                    Relax::Push
+                } else {
+                    Relax::Skip
+                }
+            },
+        );
+    }
+    // compare by reachability first, so that `unreachable` ranks behind every distance
+    let (nodes, points): (Vec<_>, Vec<Vec<_>>) = dists
+        .into_iter()
+        .map(|(node, du)| {
+            (
+                node,
+                du.into_iter().map(|d| (d == unreachable, d)).collect(),
+            )
+        })
+        .unzip();
+    Ok(skyline(&points)
+        .into_iter()
+        .map(|idx| (nodes[idx], points[idx].iter().map(|&(_, d)| d).collect()))
+        .collect())
+}
+
+/// Like [`semantic_place_skyline`], but only nodes reachable from `seeds` (including the seeds) are candidates.
+/// Distances are still computed over the whole graph, since keywords may lie outside the reachable region, but
+/// keyword nodes in weakly connected components without a seed are skipped: no candidate can reach them. The search
+/// for each keyword stops as soon as it reached every candidate.
+pub fn semantic_place_skyline_from_seeds<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    seeds: &[G::NodeId],
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let components = weak_components(graph);
+    let seed_components: HashSet<_> = seeds
+        .iter()
+        .filter_map(|seed| components.get(seed))
+        .collect();
+    let keywords_of = keywords_in(node_to_keyword);
+    let reachable = reachable_from(graph, seeds);
+    let mut dists = candidate_distances(
+        graph,
+        |node| {
+            if seed_components.contains(&components[&node]) {
+                keywords_of(node)
+            } else {
+                &[]
+            }
+        },
+        keywords,
+        |node| reachable.contains(&node),
+    );
+    dists.retain(|node, _| reachable.contains(node));
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`semantic_place_skyline`], but the nodes of `exclude` are no candidates. They still hold keywords and
+/// connect other nodes, so the distances of the remaining nodes are unchanged.
+pub fn semantic_place_skyline_excluding<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    exclude: &HashSet<G::NodeId>,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let mut dists = keyword_distances(graph, node_to_keyword, keywords);
+    dists.retain(|node, _| !exclude.contains(node));
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`semantic_place_skyline`], but only the nodes satisfying `is_candidate` are candidates, e.g. only hotels, or
+/// the members of a set with `|node| candidates.contains(&node)`. Keyword nodes still count anywhere in the graph,
+/// but the search for each keyword stops as soon as it reached every candidate.
+pub fn semantic_place_skyline_among<G, K, D, F>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    is_candidate: F,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+    F: Fn(G::NodeId) -> bool,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let mut dists =
+        candidate_distances(graph, keywords_in(node_to_keyword), keywords, &is_candidate);
+    dists.retain(|&node, _| is_candidate(node));
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`semantic_place_skyline`], but if `anchor` is given, keyword `keywords[anchor]` is mandatory: nodes that
+/// cannot reach it are no candidates, while the other keywords stay optional. The result is empty if no node
+/// reaches the anchor, unless `fallback_nearest` is set: then the node with the smallest sum of distances is
+/// returned instead, flagged as a fallback, see [`FallbackSkyline`].
+///
+/// # Panics
+///
+/// Panics if `anchor` is not an index into `keywords`.
+pub fn semantic_place_skyline_with_anchor<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    anchor: Option<usize>,
+    fallback_nearest: bool,
+) -> Result<FallbackSkyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded + ToPrimitive,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let Some(anchor) = anchor else {
+        return Ok(FallbackSkyline {
+            skyline: semantic_place_skyline(graph, node_to_keyword, keywords),
+            fallback: false,
+        });
+    };
+    // the anchor should be one of the keywords
+    assert!(anchor < keywords.len());
+    let mut dists = keyword_distances(graph, node_to_keyword, keywords);
+    if fallback_nearest && dists.values().all(|du| du[anchor] == D::max_value()) {
+        return Ok(nearest_fallback(&dists));
+    }
+    dists.retain(|_, du| du[anchor] != D::max_value());
+    Ok(FallbackSkyline {
+        skyline: minimal_elements(&dists),
+        fallback: false,
+    })
+}
+
+/// Scalar summary of a distance vector, used for ranking places. Every aggregate is computed as `f64` by
+/// [`Aggregate::score`], where unreachable keywords count as infinitely far.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Aggregate {
+    /// Sum of the distances, infinite if a keyword is unreachable.
+    Sum,
+    /// Largest distance, infinite if a keyword is unreachable.
+    Max,
+    /// Smallest distance.
+    Min,
+    /// Weighted Euclidean norm `sqrt(Σ w_i * d_i²)` of the distances cast to `f64`, with one weight per keyword. An
+    /// unreachable keyword with a positive weight makes the score infinite, which ranks the node last; a zero weight
+    /// ignores the keyword.
+    WeightedL2 { weights: Vec<f64> },
+    /// Weighted sum `Σ w_i * d_i` of the distances cast to `f64`, with one weight per keyword, and unreachable
+    /// keywords weighted as for [`Aggregate::WeightedL2`].
+    WeightedSum { weights: Vec<f64> },
+}
+
+impl Aggregate {
+    /// Applies the aggregate to a distance vector as `f64`, where unreachable keywords (`D::max_value()`) count as
+    /// infinitely far. An empty vector scores zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weights of [`Aggregate::WeightedL2`] or [`Aggregate::WeightedSum`] are not one per keyword.
+    pub fn score<D>(&self, dist: &[D]) -> f64
+    where
+        D: Copy + PartialEq + UpperBounded + ToPrimitive,
+    {
+        let to_f64 = |&d: &D| {
+            if d == D::max_value() {
+                f64::INFINITY
+            } else {
+                d.to_f64().unwrap_or(f64::INFINITY)
+            }
+        };
+        match self {
+            Aggregate::Sum => dist.iter().map(to_f64).sum(),
+            Aggregate::Max => dist.iter().map(to_f64).reduce(f64::max).unwrap_or(0.0),
+            Aggregate::Min => dist.iter().map(to_f64).reduce(f64::min).unwrap_or(0.0),
+            Aggregate::WeightedL2 { weights } => {
+                // one weight per keyword
+                assert_eq!(weights.len(), dist.len());
+                dist.iter()
+                    .zip(weights)
+                    .filter(|(_, &w)| w != 0.0)
+                    .map(|(d, w)| w * to_f64(d).powi(2))
+                    .sum::<f64>()
+                    .sqrt()
+            }
+            Aggregate::WeightedSum { weights } => {
+                // one weight per keyword
+                assert_eq!(weights.len(), dist.len());
+                dist.iter()
+                    .zip(weights)
+                    .filter(|(_, &w)| w != 0.0)
+                    .map(|(d, w)| w * to_f64(d))
+                    .sum()
+            }
+        }
+    }
+}
+
+/// Like [`semantic_place_skyline`], but annotates each skyline member with its `rank_by` score, see
+/// [`Aggregate::score`].
+pub fn semantic_place_skyline_ranked<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    rank_by: Aggregate,
+) -> Result<ScoredPlaces<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded + ToPrimitive,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    Ok(semantic_place_skyline(graph, node_to_keyword, keywords)
+        .into_iter()
+        .map(|(node, dist)| {
+            let rank = rank_by.score(&dist);
+            (node, dist, rank)
+        })
+        .collect())
+}
+
+/// Ranks every node reaching at least one of `keywords` by its `agg` score, see [`Aggregate::score`], then by node.
+/// Each entry holds the node, its distances, its score and whether it is part of the skyline.
+pub fn ranked_with_skyline_flag<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    agg: Aggregate,
+) -> Result<FlaggedRanking<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded + ToPrimitive,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let dists: DistanceMap<_, D> = keyword_distances(graph, node_to_keyword, keywords);
+    let (nodes, points): (Vec<_>, Vec<_>) = dists.into_iter().unzip();
+    let mut in_skyline = vec![false; nodes.len()];
+    for idx in skyline(&points) {
+        in_skyline[idx] = true;
+    }
+    let mut ranked: Vec<_> = nodes
+        .into_iter()
+        .zip(points)
+        .zip(in_skyline)
+        .filter(|((_, dist), _)| dist.iter().any(|&d| d != D::max_value()))
+        .map(|((node, dist), in_skyline)| {
+            let rank = agg.score(&dist);
+            (node, dist, rank, in_skyline)
+        })
+        .collect();
+    ranked.sort_unstable_by(|(u, _, rank_u, _), (v, _, rank_v, _)| {
+        rank_u.total_cmp(rank_v).then_with(|| u.cmp(v))
+    });
+    Ok(ranked)
+}
+
+/// Returns the skyline member with the smallest `agg` score, see [`Aggregate::score`], breaking ties by the
+/// smallest node. Returns `Ok(None)` only for a graph without nodes.
+pub fn best_skyline_place<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    agg: Aggregate,
+) -> Result<Option<Place<G::NodeId, D>>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded + ToPrimitive,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    Ok(semantic_place_skyline(graph, node_to_keyword, keywords)
+        .into_iter()
+        .map(|(node, dist)| (agg.score(&dist), node, dist))
+        .min_by(|(score1, node1, _), (score2, node2, _)| {
+            score1.total_cmp(score2).then_with(|| node1.cmp(node2))
+        })
+        .map(|(_, node, dist)| (node, dist)))
+}
+
+/// Returns the `k` nodes of `dists` with the smallest `agg` score, sorted by score and then by node.
+///
+/// Keeps a bounded max-heap of the current best `k`, so this takes O(n log k) time and O(k) extra memory. Scores are
+/// computed by [`Aggregate::score`].
+pub fn top_k_by_aggregate<N, D>(
+    dists: &DistanceMap<N, D>,
+    k: usize,
+    agg: Aggregate,
+) -> ScoredPlaces<N, D>
+where
+    N: Copy + Hash + Ord,
+    D: Copy + PartialEq + UpperBounded + ToPrimitive,
+{
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for (&u, du) in dists {
+        heap.push((TotalF64(agg.score(du)), u));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|(score, u)| (u, dists[&u].clone(), score.0))
+        .collect()
+}
+
+/// Returns the `k` nodes of `dists` reaching every keyword with the smallest `agg` score, see [`Aggregate::score`],
+/// sorted by score and then by node. Unlike [`top_k_by_aggregate`], this never ranks a node missing a keyword.
+pub fn top_k_by_score<N, D>(
+    dists: &DistanceMap<N, D>,
+    k: usize,
+    agg: &Aggregate,
+) -> Vec<(N, Vec<D>, f64)>
+where
+    N: Copy + Hash + Ord,
+    D: Copy + Ord + Zero + SaturatingAdd + UpperBounded + ToPrimitive,
+{
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for (&u, du) in dists {
+        if du.iter().any(|&d| d == D::max_value()) {
+            continue;
+        }
+        heap.push((TotalF64(agg.score(du)), u));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|(score, u)| (u, dists[&u].clone(), score.0))
+        .collect()
+}
+
+/// Ranks the nodes of `graph` reaching every one of `keywords` by their `agg` score instead of computing the
+/// skyline, and returns the best `k`, see [`top_k_by_score`].
+pub fn semantic_place_top_k<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    k: usize,
+    agg: &Aggregate,
+) -> Result<ScoredPlaces<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + SaturatingAdd + UpperBounded + ToPrimitive,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    Ok(top_k_by_score(
+        &keyword_distances(graph, node_to_keyword, keywords),
+        k,
+        agg,
+    ))
+}
+
+/// Selects at most `k` skyline members of `dists` that together dominate as many other nodes as possible, sorted by
+/// selection order.
+///
+/// Greedily picks the member dominating the most nodes not yet dominated by an earlier pick, breaking ties by the
+/// smaller node, which approximates the best `k` within a factor of `1 - 1/e`. Takes O(k * s * n) time for s skyline
+/// members.
+pub fn representative_skyline<N, D>(dists: &DistanceMap<N, D>, k: usize) -> Vec<(N, Vec<D>)>
+where
+    N: Copy + Ord,
+    D: Clone + Ord,
+{
+    let (nodes, points): (Vec<_>, Vec<_>) = dists.iter().map(|(u, du)| (*u, du)).unzip();
+    // nodes dominated by each skyline member
+    let mut candidates: Vec<(usize, Vec<usize>)> = skyline(&points)
+        .into_iter()
+        .map(|u| {
+            let dominated = (0..points.len())
+                .filter(|&v| partial_cmp(points[u], points[v]) == Some(Ordering::Less))
+                .collect();
+            (u, dominated)
+        })
+        .collect();
+    let mut covered = vec![false; points.len()];
+    let mut selected = Vec::new();
+    while selected.len() < k && !candidates.is_empty() {
+        let gain = |dominated: &[usize]| dominated.iter().filter(|&&v| !covered[v]).count();
+        let (best, _) = candidates
+            .iter()
+            .enumerate()
+            .max_by(|(_, (u, du)), (_, (v, dv))| {
+                gain(du)
+                    .cmp(&gain(dv))
+                    .then_with(|| nodes[*v].cmp(&nodes[*u]))
+            })
+            .unwrap();
+        let (u, dominated) = candidates.swap_remove(best);
+        for v in dominated {
+            covered[v] = true;
+        }
+        selected.push((nodes[u], points[u].clone()));
+    }
+    selected
+}
+
+/// Like [`semantic_place_skyline`], but reports distances min-max normalized per column, see [`normalize_columns`].
+///
+/// Min-max scaling is monotone in every column, so the skyline members are the same as those of
+/// [`semantic_place_skyline`]; only the reported distances are rescaled.
+pub fn semantic_place_skyline_normalized<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> Result<Skyline<G::NodeId, f64>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded + ToPrimitive,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let dists = keyword_distances::<_, _, D>(graph, node_to_keyword, keywords);
+    let skyline = minimal_elements(&dists);
+    let normalized = normalize_columns(&dists);
+    Ok(skyline
+        .into_iter()
+        .map(|(node, _)| (node, normalized[&node].clone()))
+        .collect())
+}
+
+/// Like [`semantic_place_skyline_normalized`], but annotates each skyline member with the `rank_by` score of its
+/// normalized distances, so that a keyword with large distances does not outweigh the others in the score.
+pub fn semantic_place_skyline_normalized_ranked<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    rank_by: Aggregate,
+) -> Result<ScoredPlaces<G::NodeId, f64>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded + ToPrimitive,
+{
+    Ok(
+        semantic_place_skyline_normalized::<_, _, D>(graph, node_to_keyword, keywords)?
+            .into_iter()
+            .map(|(node, dist)| {
+                let rank = rank_by.score(&dist);
+                (node, dist, rank)
+            })
+            .collect(),
+    )
+}
+
+/// Skyline over several graph layers sharing node ids, e.g. the walking and transit networks of a city. Keyword
+/// distances are computed independently on each layer, searching each layer in its given [`Direction`], so the
+/// distance vector of a node holds `keywords.len()` columns per layer, layer by layer. Nodes absent from a layer
+/// get `D::max_value()` in its columns.
+pub fn multilayer_skyline<G, K, D>(
+    layers: &[(G, Direction)],
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let num_columns = layers.len() * keywords.len();
+    let mut dists: DistanceMap<G::NodeId, D> = DistanceMap::default();
+    for (layer_idx, &(graph, direction)) in layers.iter().enumerate() {
+        let layer_dists: DistanceMap<_, D> = keyword_distances_by(
+            graph,
+            keywords_in(node_to_keyword),
+            keywords.len(),
+            |keyword_idx, node_keywords| {
+                node_keywords.binary_search(&keywords[keyword_idx]).is_ok()
+            },
+            None,
+            &[direction],
+        );
+        let offset = layer_idx * keywords.len();
+        for (node, layer_dv) in layer_dists {
+            dists
+                .entry(node)
+                .or_insert_with(|| vec![D::max_value(); num_columns])
+                [offset..offset + keywords.len()]
+                .copy_from_slice(&layer_dv);
+        }
+    }
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`semantic_place_skyline`], but reports proximities instead of distances, see [`proximity_columns`].
+///
+/// Proximity is strictly decreasing in the distance, so the skyline maximizing proximities ([`Objective::Max`]
+/// in every column) has the same members as the one minimizing distances; only the reported values differ.
+pub fn semantic_place_skyline_proximity<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> Result<Skyline<G::NodeId, f64>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded + ToPrimitive,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let dists = keyword_distances::<_, _, D>(graph, node_to_keyword, keywords);
+    let skyline = minimal_elements(&dists);
+    let proximity = proximity_columns(&dists);
+    Ok(skyline
+        .into_iter()
+        .map(|(node, _)| (node, proximity[&node].clone()))
+        .collect())
+}
+
+/// Calculates the distance from each node to the nearest node containing each keyword. Unreachable keywords are
+/// reported as `D::max_value()`. Each entry of `node_to_keyword` should be sorted.
+///
+/// On a directed acyclic graph, every keyword is settled in a single sweep in reverse topological order instead of
+/// one search per keyword.
+pub fn keyword_distances<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> DistanceMap<G::NodeId, D>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    if let Some(dists) = dag_keyword_distances(graph, node_to_keyword, keywords) {
+        return dists;
+    }
+    keyword_distances_by(
+        graph,
+        keywords_in(node_to_keyword),
+        keywords.len(),
+        |keyword_idx, node_keywords| node_keywords.binary_search(&keywords[keyword_idx]).is_ok(),
+        None,
+        &[Direction::Incoming],
+    )
+}
+
+/// Fast path of [`keyword_distances`] for a directed acyclic graph: with the nodes ordered sinks first, each node's
+/// distances follow from those of its successors, all settled before it. Returns `None` if the graph has a cycle.
+fn dag_keyword_distances<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> Option<DistanceMap<G::NodeId, D>>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // successors not ordered yet
+    let mut num_pending: HashMap<_, usize> = graph
+        .node_identifiers()
+        .map(|node| {
+            (
+                node,
+                graph.neighbors_directed(node, Direction::Outgoing).count(),
+            )
+        })
+        .collect();
+    let mut ready: Vec<_> = num_pending
+        .iter()
+        .filter(|(_, &count)| count == 0)
+        .map(|(&node, _)| node)
+        .collect();
+    let mut order = Vec::with_capacity(num_pending.len());
+    while let Some(node) = ready.pop() {
+        order.push(node);
+        for pred in graph.neighbors_directed(node, Direction::Incoming) {
+            let count = num_pending.get_mut(&pred)?;
+            *count -= 1;
+            if *count == 0 {
+                ready.push(pred);
+            }
+        }
+    }
+    // the nodes of a cycle never become ready
+    if order.len() < num_pending.len() {
+        return None;
+    }
+    let keywords_of = keywords_in(node_to_keyword);
+    let mut dists = DistanceMap::with_capacity(order.len());
+    for node in order {
+        let node_keywords = keywords_of(node);
+        let du = keywords
+            .iter()
+            .enumerate()
+            .map(|(keyword_idx, keyword)| {
+                if node_keywords.binary_search(keyword).is_ok() {
+                    return D::zero();
+                }
+                match graph
+                    .neighbors_directed(node, Direction::Outgoing)
+                    .map(|succ| dists[&succ][keyword_idx])
+                    .min()
+                {
+                    Some(d) if d != D::max_value() => d + D::one(),
+                    _ => D::max_value(),
+                }
+            })
+            .collect();
+        dists.insert(node, du);
+    }
+    Some(dists)
+}
+
+/// Same result as [`keyword_distances`], but all keywords are searched together: the frontier holds each node once with
+/// the columns it improved at the current level, so the neighbors of a node containing several queried keywords are
+/// iterated once per level instead of once per keyword. This pays off when most nodes carry many of the keywords.
+pub fn keyword_distances_combined<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> DistanceMap<G::NodeId, D>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // initialize keyword distances
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); keywords.len()]))
+        .collect();
+    // seed the frontier with each node and the columns of the keywords it contains
+    let mut frontier: Vec<(G::NodeId, Vec<usize>)> = Vec::new();
+    for node in graph.node_identifiers() {
+        let Some(node_keywords) = node_to_keyword.get(&node) else {
+            continue;
+        };
+        let columns: Vec<_> = (0..keywords.len())
+            .filter(|&idx| node_keywords.binary_search(&keywords[idx]).is_ok())
+            .collect();
+        if !columns.is_empty() {
+            let node_dists = dists.get_mut(&node).unwrap();
+            for &col in &columns {
+                node_dists[col] = D::zero();
+            }
+            frontier.push((node, columns));
+        }
+    }
+    // advance all columns one level at a time
+    let mut dist = D::zero();
+    while !frontier.is_empty() {
+        dist = dist + D::one();
+        let mut next: HashMap<G::NodeId, Vec<usize>, SearchHasher> = HashMap::default();
+        for (current, columns) in &frontier {
+            for nbr in graph.neighbors_directed(*current, Direction::Incoming) {
+                let nbr_dists = dists.get_mut(&nbr).unwrap();
+                for &col in columns {
+                    if dist < nbr_dists[col] {
+                        nbr_dists[col] = dist;
+                        next.entry(nbr).or_default().push(col);
+                    }
+                }
+            }
+        }
+        frontier = next.into_iter().collect();
+    }
+    dists
+}
+
+/// Approximates [`semantic_place_skyline`] by running the dominance filter over a random sample of about
+/// `sample_frac` of the nodes, with the keyword distances still computed over the whole graph. The result is the
+/// skyline of the sample: its members may be dominated by nodes left out, and skyline nodes left out are missed.
+///
+/// Whether a node is sampled depends only on `seed` and the hash of the node, so the same seed gives the same result
+/// across processes. The hash function is the crate's own Fx hash, but the node is fed to it by its `Hash` impl: for
+/// integer ids this writes the id as a whole integer, so the sample also holds across platforms and releases of the
+/// standard library, while other node types carry no such guarantee.
+///
+/// # Panics
+///
+/// Panics if `sample_frac` lies outside `[0, 1]`.
+pub fn approximate_skyline<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+    sample_frac: f64,
+    seed: u64,
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    // the sample fraction should lie in [0, 1]
+    assert!((0.0..=1.0).contains(&sample_frac));
+    let mut dists = keyword_distances(graph, node_to_keyword, keywords);
+    dists.retain(|node, _| {
+        // the in-crate Fx hash, so the sample does not depend on the map order
+        let mut hasher = fxhash::FxHasher::default();
+        hasher.write_u64(seed);
+        node.hash(&mut hasher);
+        ((hasher.finish() >> 11) as f64 / (1u64 << 53) as f64) < sample_frac
+    });
+    Ok(minimal_elements(&dists))
+}
+
+/// Like [`keyword_distances`], but over any graph given by closures: `nodes` are all the nodes, `neighbors(node)`
+/// are the nodes one hop closer to `node` than to the sources (the in-neighbors in [`keyword_distances`]), and
+/// `is_source(node, keyword_idx)` tells whether `node` contains keyword `keyword_idx`. Every neighbor should be one of
+/// `nodes`.
+pub fn keyword_distances_with<N, D, I>(
+    nodes: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(N) -> I,
+    is_source: impl Fn(N, usize) -> bool,
+    num_keywords: usize,
+) -> DistanceMap<N, D>
+where
+    N: Copy + Hash + Eq,
+    D: Copy + Ord + Zero + One + UpperBounded,
+    I: IntoIterator<Item = N>,
+{
+    let nodes: Vec<_> = nodes.into_iter().collect();
+    // initialize keyword distances
+    let mut dists: DistanceMap<_, _> = nodes
+        .iter()
+        .map(|&node| (node, vec![D::max_value(); num_keywords]))
+        .collect();
+    for keyword_idx in 0..num_keywords {
+        let queue = nodes
+            .iter()
+            .filter(|&&node| is_source(node, keyword_idx))
+            .map(|&node| {
+                dists.get_mut(&node).unwrap()[keyword_idx] = D::zero();
+                (node, D::zero(), ())
+            })
+            .collect();
+        bfs_from(queue, D::max_value(), &mut neighbors, |_, _, nbr, dist| {
+            relax_min(&mut dists.get_mut(&nbr).unwrap()[keyword_idx], dist)
+        });
+    }
+    dists
+}
+
+/// Like [`semantic_place_skyline`], but the keywords of each node are read through `keywords_of`, as in
+/// [`keyword_distances_with_accessor`].
+pub fn semantic_place_skyline_with_accessor<'a, G, K, D, A>(
+    graph: G,
+    keywords_of: A,
+    keywords: &[K],
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord + 'a,
+    D: Copy + Ord + Zero + One + UpperBounded,
+    A: Fn(G::NodeId) -> &'a [K],
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let dists = keyword_distances_with_accessor(graph, keywords_of, keywords);
+    Ok(minimal_elements(&dists))
+}
+
+/// Calculates the distance from each node to the nearest node holding any keyword, as a single column.
+pub fn any_keyword_distances<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+) -> DistanceMap<G::NodeId, D>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    keyword_distances_by(
+        graph,
+        keywords_in(node_to_keyword),
+        1,
+        |_, node_keywords| !node_keywords.is_empty(),
+        None,
+        &[Direction::Incoming],
+    )
+}
+
+/// Skyline of [`any_keyword_distances`], i.e. every node of the graph holding a keyword, at distance zero. Without
+/// any such node, all nodes are equally unreachable and form the skyline.
+pub fn semantic_place_skyline_any_keyword<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+) -> Vec<(G::NodeId, Vec<D>)>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    minimal_elements(&any_keyword_distances(graph, node_to_keyword))
+}
+
+/// Like [`keyword_distances`], but also reports the nearest node holding each keyword, `None` if unreachable.
+/// When several keyword nodes are equally near, the smallest one is reported, independent of traversal order.
+pub fn keyword_distances_with_sources<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> DistanceMap<G::NodeId, (D, Option<G::NodeId>)>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Ord,
+    K: Ord,
+    D: Copy + Ord + Zero + One + UpperBounded,
+{
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![(D::max_value(), None); keywords.len()]))
+        .collect();
+    let keywords_of = keywords_in(node_to_keyword);
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        let queue = graph
+            .node_identifiers()
+            .filter(|&node| keywords_of(node).binary_search(keyword).is_ok())
+            .map(|node| {
+                dists.get_mut(&node).unwrap()[keyword_idx] = (D::zero(), Some(node));
+                (node, D::zero(), ())
+            })
+            .collect();
+        // the queue holds one distance level after the other, so every node of a level has its final source before
+        // the next level is expanded
+        bfs_from(
+            queue,
+            D::max_value(),
+            |current| graph.neighbors_directed(current, Direction::Incoming),
+            |current, _, nbr, dist| {
+                let source = dists[&current][keyword_idx].1;
+                let (nbr_dist, nbr_source) = &mut dists.get_mut(&nbr).unwrap()[keyword_idx];
+                if dist < *nbr_dist {
+                    *nbr_dist = dist;
+                    *nbr_source = source;
+                    Relax::Push
+                } else {
+                    // ties go to the smallest source
+                    if dist == *nbr_dist && source < *nbr_source {
+                        *nbr_source = source;
+                    }
+                    Relax::Skip
+                }
+            },
+        );
+    }
+    dists
+}
+
+/// Like [`keyword_distances`], but the keywords of each node are read through `keywords_of`, which should return a
+/// sorted slice (empty for nodes without keywords). This allows backing them by any storage, e.g. one shared arena.
+pub fn keyword_distances_with_accessor<'a, G, K, D, A>(
+    graph: G,
+    keywords_of: A,
+    keywords: &[K],
+) -> DistanceMap<G::NodeId, D>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    K: Ord + 'a,
+    D: Copy + Ord + Zero + One + UpperBounded,
+    A: Fn(G::NodeId) -> &'a [K],
+{
+    keyword_distances_by(
+        graph,
+        keywords_of,
+        keywords.len(),
+        |keyword_idx, node_keywords| node_keywords.binary_search(&keywords[keyword_idx]).is_ok(),
+        None,
+        &[Direction::Incoming],
+    )
+}
+
+/// Like [`keyword_distances_with_accessor`], but only the distances of the nodes satisfying `is_candidate` are
+/// exact: the search for each keyword stops once it reached every candidate, so other nodes may keep larger
+/// distances. A candidate's distance is final when the search first reaches it, so counting them down suffices.
+fn candidate_distances<'a, G, K, D, A, F>(
+    graph: G,
+    keywords_of: A,
+    keywords: &[K],
+    is_candidate: F,
+) -> DistanceMap<G::NodeId, D>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    K: Ord + 'a,
+    D: Copy + Ord + Zero + One + UpperBounded,
+    A: Fn(G::NodeId) -> &'a [K],
+    F: Fn(G::NodeId) -> bool,
+{
+    let num_candidates = graph
+        .node_identifiers()
+        .filter(|&node| is_candidate(node))
+        .count();
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); keywords.len()]))
+        .collect();
+    for (keyword_idx, keyword) in keywords.iter().enumerate() {
+        // candidates whose distance is not final yet
+        let mut remaining = num_candidates;
+        let queue: VecDeque<_> = graph
+            .node_identifiers()
+            .filter(|&node| keywords_of(node).binary_search(keyword).is_ok())
+            .map(|node| {
+                dists.get_mut(&node).unwrap()[keyword_idx] = D::zero();
+                remaining -= usize::from(is_candidate(node));
+                (node, D::zero(), ())
+            })
+            .collect();
+        if remaining == 0 {
+            continue;
+        }
+        bfs_from(
+            queue,
+            D::max_value(),
+            |current| graph.neighbors_directed(current, Direction::Incoming),
+            |_, _, nbr, dist| {
+                let relax = relax_min(&mut dists.get_mut(&nbr).unwrap()[keyword_idx], dist);
+                if matches!(relax, Relax::Push) && is_candidate(nbr) {
+                    remaining -= 1;
+                    if remaining == 0 {
+                        return Relax::Stop;
+                    }
+                }
+                relax
+            },
+        );
+    }
+    dists
+}
+
+/// Reads the keywords of a node from `node_to_keyword`.
+fn keywords_in<'a, N: Hash + Eq, K>(
+    node_to_keyword: &'a HashMap<N, Vec<K>>,
+) -> impl Fn(N) -> &'a [K] + 'a {
+    move |node| node_to_keyword.get(&node).map_or(&[], Vec::as_slice)
+}
+
+/// Calculates the distance from each node to the nearest node of each column. A node is a source of column
+/// `keyword_idx` if `is_source(keyword_idx, keywords_of(node))` holds. If `max_dist` is given, column `keyword_idx` is
+/// not explored beyond `max_dist[keyword_idx]`. The search from the sources follows each of `directions`.
+fn keyword_distances_by<'a, G, K, D, A, F>(
+    graph: G,
+    keywords_of: A,
+    num_keywords: usize,
+    is_source: F,
+    max_dist: Option<&[D]>,
+    directions: &[Direction],
+) -> DistanceMap<G::NodeId, D>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    K: 'a,
+    D: Copy + Ord + Zero + One + UpperBounded,
+    A: Fn(G::NodeId) -> &'a [K],
+    F: Fn(usize, &[K]) -> bool,
+{
+    // initialize keyword distances
+    let mut dists: DistanceMap<_, _, SearchHasher> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); num_keywords]))
+        .collect();
+    // for each keyword, calculate the distance from each node to the nodes containing it
+    for keyword_idx in 0..num_keywords {
+        let sources = graph
+            .node_identifiers()
+            .filter(|&node| is_source(keyword_idx, keywords_of(node)));
+        let max_dist = max_dist.map_or_else(D::max_value, |max_dist| max_dist[keyword_idx]);
+        multi_source_bfs(
+            graph,
+            &mut dists,
+            keyword_idx,
+            sources,
+            max_dist,
+            directions,
+        );
+    }
+    into_distance_map(dists)
+}
+
+/// Fills column `keyword_idx` of `dists` with the distance to the nearest of `sources`, leaving nodes farther than
+/// `max_dist` untouched. The search expands from each node to its neighbors in each of `directions`, so
+/// `Direction::Incoming` measures the distance from each node to the sources.
+fn multi_source_bfs<G, D, S>(
+    graph: G,
+    dists: &mut DistanceMap<G::NodeId, D, S>,
+    keyword_idx: usize,
+    sources: impl IntoIterator<Item = G::NodeId>,
+    max_dist: D,
+    directions: &[Direction],
+) where
+    G: IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    D: Copy + Ord + Zero + One,
+    S: BuildHasher,
+{
+    let sources: Vec<_> = sources.into_iter().collect();
+    for node in &sources {
+        dists.get_mut(node).unwrap()[keyword_idx] = D::zero();
+    }
+    resume_bfs(graph, dists, keyword_idx, sources, max_dist, directions);
+}
+
+/// Continues the search of [`multi_source_bfs`] in column `keyword_idx` from `seeds`, each at its current distance,
+/// e.g. after a new edge brought some node closer to the sources.
+fn resume_bfs<G, D, S>(
+    graph: G,
+    dists: &mut DistanceMap<G::NodeId, D, S>,
+    keyword_idx: usize,
+    seeds: impl IntoIterator<Item = G::NodeId>,
+    max_dist: D,
+    directions: &[Direction],
+) where
+    G: IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+    D: Copy + Ord + Zero + One,
+    S: BuildHasher,
+{
+    let queue = seeds
+        .into_iter()
+        .map(|node| (node, dists[&node][keyword_idx], ()))
+        .collect();
+    bfs_from(
+        queue,
+        max_dist,
+        |current| {
+            directions
+                .iter()
+                .flat_map(move |&direction| graph.neighbors_directed(current, direction))
+        },
+        |_, _, nbr, dist| relax_min(&mut dists.get_mut(&nbr).unwrap()[keyword_idx], dist),
+    );
+}
+
+/// Like [`multi_source_bfs`], but for edges of any non-negative length: each of `sources` starts at its own
+/// distance, and `neighbors(current, dist)` yields each neighbor of `current` with its distance through `current`,
+/// which is at `dist`. Nodes are settled in order of distance, so a node popped at its current distance is final.
+fn multi_source_dijkstra<N, D, S, I>(
+    dists: &mut DistanceMap<N, D, S>,
+    keyword_idx: usize,
+    sources: impl IntoIterator<Item = (N, D)>,
+    mut neighbors: impl FnMut(N, D) -> I,
+) where
+    N: Copy + Hash + Ord,
+    D: Copy + Ord,
+    S: BuildHasher,
+    I: IntoIterator<Item = (N, D)>,
+{
+    let mut heap = BinaryHeap::new();
+    for (node, dist) in sources {
+        if let Relax::Push = relax_min(&mut dists.get_mut(&node).unwrap()[keyword_idx], dist) {
+            heap.push(Reverse((dist, node)));
+        }
+    }
+    while let Some(Reverse((dist, current))) = heap.pop() {
+        // skip stale entries of nodes improved after they were pushed
+        if dist > dists[&current][keyword_idx] {
+            continue;
+        }
+        for (nbr, nbr_dist) in neighbors(current, dist) {
+            if let Relax::Push = relax_min(&mut dists.get_mut(&nbr).unwrap()[keyword_idx], nbr_dist)
+            {
+                heap.push(Reverse((nbr_dist, nbr)));
+            }
+        }
+    }
+}
+
+/// What [`bfs_from`] does with a neighbor it reached.
+pub(crate) enum Relax {
+    /// Leave the neighbor alone.
+    Skip,
+    /// Queue the neighbor, so that it is expanded in turn.
+    Push,
+    /// End the search.
+    Stop,
+}
+
+/// Lowers `nbr_dist` to `dist` and queues the neighbor if that is an improvement, the relaxation of a plain search.
+pub(crate) fn relax_min<D: Ord>(nbr_dist: &mut D, dist: D) -> Relax {
+    if dist < *nbr_dist {
+        *nbr_dist = dist;
+        Relax::Push
+    } else {
+        Relax::Skip
+    }
+}
+
+/// Breadth-first search shared by the keyword searches. `queue` holds the sources with their distance and a tag,
+/// e.g. the keyword node they spread from. Each node closer than `max_dist` is expanded to its `neighbors`, and
+/// `relax(current, tag, nbr, dist)` decides what happens to each neighbor `nbr` of `current`, one hop farther at
+/// `dist`. A pushed neighbor inherits the tag of `current`. Returns `false` if `relax` stopped the search.
+pub(crate) fn bfs_from<N, D, T, I>(
+    mut queue: VecDeque<(N, D, T)>,
+    max_dist: D,
+    mut neighbors: impl FnMut(N) -> I,
+    mut relax: impl FnMut(N, T, N, D) -> Relax,
+) -> bool
+where
+    N: Copy,
+    D: Copy + Ord + Zero + One,
+    T: Copy,
+    I: IntoIterator<Item = N>,
+{
+    while let Some((current, dist, tag)) = queue.pop_front() {
+        if dist >= max_dist {
+            continue;
+        }
+        let dist = dist + D::one();
+        for nbr in neighbors(current) {
+            match relax(current, tag, nbr, dist) {
+                Relax::Skip => {}
+                Relax::Push => queue.push_back((nbr, dist, tag)),
+                Relax::Stop => return false,
+            }
+        }
+    }
+    true
+}
+
+/// Estimates the work of a query over `num_nodes` nodes and `num_keywords` keywords, in distance updates and
+/// comparisons: `num_nodes * num_keywords` for the keyword searches plus `num_nodes * num_nodes` for the naive
+/// skyline filter. Edges are not counted, so the estimate assumes a sparse graph. Saturates at `u64::MAX`.
+pub fn estimated_work(num_nodes: usize, num_keywords: usize) -> u64 {
+    let num_nodes = num_nodes as u64;
+    let bfs = num_nodes.saturating_mul(num_keywords as u64);
+    let filter = num_nodes.saturating_mul(num_nodes);
+    bfs.saturating_add(filter)
+}
+
+/// Estimates the bytes held by a [`DistanceMap`] of `num_nodes` nodes and `num_keywords` keywords: the distance
+/// vectors themselves plus the hash table, assuming a load factor of 7/8 and one control byte per bucket.
+pub fn estimated_distance_map_bytes<N, D>(num_nodes: usize, num_keywords: usize) -> usize {
+    let buckets = if num_nodes == 0 {
+        0
+    } else {
+        (num_nodes * 8 / 7 + 1).next_power_of_two()
+    };
+    let table = buckets * (size_of::<(N, Vec<D>)>() + 1);
+    table + num_nodes * num_keywords * size_of::<D>()
+}
+
+/// Collects the nodes reachable from `seeds` along outgoing edges, including the seeds.
+fn reachable_from<G>(graph: G, seeds: &[G::NodeId]) -> HashSet<G::NodeId>
+where
+    G: IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+{
+    let mut reachable: HashSet<_> = seeds.iter().copied().collect();
+    let mut queue: VecDeque<_> = reachable.iter().copied().collect();
+    while let Some(current) = queue.pop_front() {
+        for nbr in graph.neighbors_directed(current, Direction::Outgoing) {
+            if reachable.insert(nbr) {
+                queue.push_back(nbr);
+            }
+        }
+    }
+    reachable
+}
+
+/// Labels each node with the index of its weakly connected component.
+fn weak_components<G>(graph: G) -> HashMap<G::NodeId, usize>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+    G::NodeId: Hash + Eq,
+{
+    let mut components = HashMap::new();
+    let mut num_components = 0;
+    let mut queue = VecDeque::new();
+    for node in graph.node_identifiers() {
+        if components.contains_key(&node) {
+            continue;
+        }
+        components.insert(node, num_components);
+        queue.push_back(node);
+        while let Some(current) = queue.pop_front() {
+            for direction in [Direction::Outgoing, Direction::Incoming] {
+                for nbr in graph.neighbors_directed(current, direction) {
+                    if let Entry::Vacant(entry) = components.entry(nbr) {
+                        entry.insert(num_components);
+                        queue.push_back(nbr);
+                    }
+                }
+            }
+        }
+        num_components += 1;
+    }
+    components
+}
+
+/// Iterates over the nodes of `dists` that can reach keyword `keyword_idx`, i.e. whose distance to it is not
+/// `D::max_value()`.
+pub fn reachable_nodes<N, D>(
+    dists: &DistanceMap<N, D>,
+    keyword_idx: usize,
+) -> impl Iterator<Item = N> + '_
+where
+    N: Copy,
+    D: Copy + Eq + UpperBounded,
+{
+    dists
+        .iter()
+        .filter(move |(_, du)| du[keyword_idx] != D::max_value())
+        .map(|(u, _)| *u)
+}
+
+/// Counts, for each keyword, the nodes of `dists` that can reach it.
+pub fn coverage<N, D>(dists: &DistanceMap<N, D>) -> Vec<usize>
+where
+    N: Copy,
+    D: Copy + Eq + UpperBounded,
+{
+    let num_keywords = dists.values().next().map_or(0, Vec::len);
+    (0..num_keywords)
+        .map(|keyword_idx| reachable_nodes(dists, keyword_idx).count())
+        .collect()
+}
+
+/// Fraction of the keywords each node of `dists` can reach; `1.0` means it reaches all of them, as does a node
+/// without columns.
+pub fn coverage_ratio<N, D>(dists: &DistanceMap<N, D>) -> HashMap<N, f64>
+where
+    N: Copy + Hash + Eq,
+    D: Copy + Eq + UpperBounded,
+{
+    dists
+        .iter()
+        .map(|(&u, du)| {
+            let reachable = du.iter().filter(|&&d| d != D::max_value()).count();
+            let ratio = if du.is_empty() {
+                1.0
+            } else {
+                reachable as f64 / du.len() as f64
+            };
+            (u, ratio)
+        })
+        .collect()
+}
+
+/// Largest finite distance of each column of `dists`, i.e. how far the farthest node that can reach the keyword is
+/// from it. `None` if no node can reach the keyword.
+pub fn max_finite_distance<N, D>(dists: &DistanceMap<N, D>) -> Vec<Option<D>>
+where
+    D: Copy + Ord + UpperBounded,
+{
+    let num_keywords = dists.values().next().map_or(0, Vec::len);
+    (0..num_keywords)
+        .map(|keyword_idx| {
+            dists
+                .values()
+                .map(|du| du[keyword_idx])
+                .filter(|&d| d != D::max_value())
+                .max()
+        })
+        .collect()
+}
+
+/// Min-max normalizes each column of `dists` to `[0, 1]`, using the minimal and maximal finite distance of the
+/// column. Unreachable distances (`D::max_value()`) become `f64::INFINITY`, and a column whose finite distances
+/// are all equal maps them to `0`.
+pub fn normalize_columns<N, D>(dists: &DistanceMap<N, D>) -> DistanceMap<N, f64>
+where
+    N: Copy + Hash + Eq,
+    D: Copy + Ord + UpperBounded + ToPrimitive,
+{
+    let num_keywords = dists.values().next().map_or(0, Vec::len);
+    let bounds: Vec<_> = (0..num_keywords)
+        .map(|keyword_idx| {
+            let finite = dists
+                .values()
+                .map(|du| du[keyword_idx])
+                .filter(|&d| d != D::max_value());
+            let min = finite.clone().min();
+            let max = finite.max();
+            min.zip(max)
+                .map(|(min, max)| (min.to_f64().unwrap(), max.to_f64().unwrap()))
+        })
+        .collect();
+    dists
+        .iter()
+        .map(|(u, du)| {
+            let normalized = du
+                .iter()
+                .zip(&bounds)
+                .map(|(&d, bound)| match bound {
+                    Some((min, max)) if d != D::max_value() => {
+                        if max > min {
+                            (d.to_f64().unwrap() - min) / (max - min)
+                        } else {
+                            0.0
+                        }
+                    }
+                    _ => f64::INFINITY,
+                })
+                .collect();
+            (*u, normalized)
+        })
+        .collect()
+}
+
+/// Rebases the distances of `results` on `baseline`, the distance vector of a reference node: each distance becomes
+/// `d - baseline[i]`, so positive deltas are worse than the baseline and negative ones better. A delta is `None`
+/// if either distance is unreachable or the delta does not fit an `i64`. Only the reported numbers change, not which nodes are reported.
+pub fn relativize<N, D>(results: &[(N, Vec<D>)], baseline: &[D]) -> Vec<(N, Vec<Option<i64>>)>
+where
+    N: Copy,
+    D: Copy + Eq + UpperBounded + ToPrimitive,
+{
+    results
+        .iter()
+        .map(|(u, du)| {
+            // one baseline distance per keyword
+            assert_eq!(du.len(), baseline.len());
+            let deltas = du
+                .iter()
+                .zip(baseline)
+                .map(|(&d, &b)| {
+                    if d == D::max_value() || b == D::max_value() {
+                        return None;
+                    }
+                    d.to_i64()?.checked_sub(b.to_i64()?)
+                })
+                .collect();
+            (*u, deltas)
+        })
+        .collect()
+}
+
+/// Converts each distance `d` of `dists` to the proximity `1 / (1 + d)`, where larger is better. Unreachable
+/// distances (`D::max_value()`) have proximity `0`, below any reachable one.
+pub fn proximity_columns<N, D>(dists: &DistanceMap<N, D>) -> DistanceMap<N, f64>
+where
+    N: Copy + Hash + Eq,
+    D: Copy + Eq + UpperBounded + ToPrimitive,
+{
+    dists
+        .iter()
+        .map(|(&u, du)| {
+            let proximity = du
+                .iter()
+                .map(|&d| {
+                    if d == D::max_value() {
+                        0.0
+                    } else {
+                        1.0 / (1.0 + d.to_f64().unwrap())
+                    }
+                })
+                .collect();
+            (u, proximity)
+        })
+        .collect()
+}
+
+/// Whether smaller or larger values of a column are better.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Objective {
+    /// Smaller is better, as for distances.
+    #[default]
+    Min,
+    /// Larger is better, e.g. for occurrence counts.
+    Max,
+}
+
+/// Column value ordered so that smaller is better under either [`Objective`]. Only values of the same objective
+/// are meant to be compared, which holds within a column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Oriented<D> {
+    /// Value of an [`Objective::Min`] column.
+    Min(D),
+    /// Value of an [`Objective::Max`] column.
+    Max(Reverse<D>),
+}
+
+impl<D> Oriented<D> {
+    /// Wraps `value` according to `objective`.
+    pub fn new(value: D, objective: Objective) -> Self {
+        match objective {
+            Objective::Min => Oriented::Min(value),
+            Objective::Max => Oriented::Max(Reverse(value)),
+        }
+    }
+
+    /// Unwraps the original value.
+    pub fn into_inner(self) -> D {
+        match self {
+            Oriented::Min(value) | Oriented::Max(Reverse(value)) => value,
+        }
+    }
+}
+
+/// Orients column `i` of `dists` by `objectives[i]`, so that the usual minimization, e.g. [`minimal_elements`],
+/// maximizes the [`Objective::Max`] columns.
+pub fn orient_columns<N, D>(
+    dists: &DistanceMap<N, D>,
+    objectives: &[Objective],
+) -> DistanceMap<N, Oriented<D>>
+where
+    N: Copy + Hash + Eq,
+    D: Copy,
+{
+    dists
+        .iter()
+        .map(|(&u, du)| {
+            // one objective per column
+            assert_eq!(du.len(), objectives.len());
+            let oriented = du
+                .iter()
+                .zip(objectives)
+                .map(|(&d, &objective)| Oriented::new(d, objective))
+                .collect();
+            (u, oriented)
+        })
+        .collect()
+}
+
+/// Skyline of `dists` where column `i` is minimized or maximized according to `objectives[i]`, see
+/// [`orient_columns`]. Reports the original values.
+pub fn skyline_with_objectives<N, D>(
+    dists: &DistanceMap<N, D>,
+    objectives: &[Objective],
+) -> Vec<(N, Vec<D>)>
+where
+    N: Copy + Hash + Eq,
+    D: Copy + Ord,
+{
+    minimal_elements(&orient_columns(dists, objectives))
+        .into_iter()
+        .map(|(u, du)| (u, du.into_iter().map(Oriented::into_inner).collect()))
+        .collect()
+}
+
+/// Builds the dominance relation among the nodes of `dists`: there is an edge `u -> v` iff the distance vector of
+/// `u` dominates that of `v`. The skyline is the set of nodes without incoming edges.
+///
+/// This compares every pair of nodes and so takes O(n²) time and up to O(n²) edges; restrict `dists` to a
+/// candidate subset on large graphs.
+pub fn dominance_graph<N, D>(dists: &DistanceMap<N, D>) -> DiGraphMap<N, ()>
+where
+    N: NodeTrait,
+    D: Ord,
+{
+    let mut graph = DiGraphMap::with_capacity(dists.len(), 0);
+    for &u in dists.keys() {
+        graph.add_node(u);
+    }
+    for (&u, du) in dists {
+        for (&v, dv) in dists {
+            if partial_cmp(du, dv) == Some(Ordering::Less) {
+                graph.add_edge(u, v, ());
+            }
+        }
+    }
+    graph
+}
+
+/// Computes the skyline of `dists` restricted to the keyword columns `columns`.
+pub fn skyline_subspace<N, D>(dists: &DistanceMap<N, D>, columns: &[usize]) -> Vec<N>
+where
+    N: Copy,
+    D: Copy + Ord,
+{
+    let (nodes, points): (Vec<_>, Vec<Vec<_>>) = dists
+        .iter()
+        .map(|(u, du)| (*u, columns.iter().map(|&col| du[col]).collect()))
+        .unzip();
+    skyline(&points).into_iter().map(|idx| nodes[idx]).collect()
+}
+
+/// Computes the skyline of every non-empty subset of the keyword columns of `dists` (the skycube), keyed by the
+/// sorted column indices of the subset.
+///
+/// There are 2^d - 1 subsets for d keywords, each costing a full skyline computation, so this is only practical
+/// for a handful of keywords.
+///
+/// # Panics
+///
+/// Panics if `dists` has as many columns as `usize` has bits.
+pub fn skycube<N, D>(dists: &DistanceMap<N, D>) -> HashMap<Vec<usize>, Vec<N>>
+where
+    N: Copy,
+    D: Copy + Ord,
+{
+    let num_keywords = dists.values().next().map_or(0, Vec::len);
+    assert!(num_keywords < usize::BITS as usize);
+    (1..1usize << num_keywords)
+        .map(|mask| {
+            let columns: Vec<_> = (0..num_keywords)
+                .filter(|col| mask & (1 << col) != 0)
+                .collect();
+            let skyline = skyline_subspace(dists, &columns);
+            (columns, skyline)
+        })
+        .collect()
+}
+
+/// Computes the k-dominant skyline of `dists`: the nodes not k-dominated by any other node, where `u` k-dominates
+/// `v` if `u` is at most as far as `v` from at least `k` of the keywords, and closer to one of them.
+///
+/// With `k` equal to the number of keywords this is the usual skyline; a smaller `k` prunes more nodes. k-dominance
+/// is not transitive, so the result may be empty. Takes O(n² * d) time for d keywords.
+///
+/// # Panics
+///
+/// Panics if `k` is zero or exceeds the number of keywords.
+pub fn k_dominant_skyline<N, D>(dists: &DistanceMap<N, D>, k: usize) -> Vec<(N, Vec<D>)>
+where
+    N: Copy,
+    D: Clone + Ord,
+{
+    let num_keywords = dists.values().next().map_or(k, Vec::len);
+    assert!(k >= 1 && k <= num_keywords);
+    let k_dominates = |du: &[D], dv: &[D]| {
+        let not_farther = du.iter().zip(dv).filter(|(d1, d2)| d1 <= d2).count();
+        not_farther >= k && du.iter().zip(dv).any(|(d1, d2)| d1 < d2)
+    };
+    dists
+        .iter()
+        .filter(|(_, dv)| dists.values().all(|du| !k_dominates(du, dv)))
+        .map(|(&v, dv)| (v, dv.clone()))
+        .collect()
+}
+
+/// Computes the skyline of the two keyword columns of `dists` as a staircase: sorted by ascending distance to the
+/// first keyword, hence by descending distance to the second, with ties broken by node. Fails with
+/// [`SkylineError::KeywordCount`] unless `dists` has exactly two columns.
+pub fn skyline_staircase<N, D>(dists: &DistanceMap<N, D>) -> Result<Vec<(N, [D; 2])>, SkylineError>
+where
+    N: Copy + Ord,
+    D: Copy + Ord,
+{
+    let num_keywords = dists.values().next().map_or(2, Vec::len);
+    if num_keywords != 2 {
+        return Err(SkylineError::KeywordCount {
+            expected: 2,
+            found: num_keywords,
+        });
+    }
+    let mut points: Vec<_> = dists.iter().map(|(&u, du)| (u, [du[0], du[1]])).collect();
+    points.sort_unstable_by(|(u, du), (v, dv)| du.cmp(dv).then_with(|| u.cmp(v)));
+    // a point is in the skyline iff no point before it has a smaller second distance, or it equals the last one kept
+    let mut staircase: Vec<(N, [D; 2])> = Vec::new();
+    for (u, du) in points {
+        match staircase.last() {
+            Some((_, last)) if du[1] >= last[1] && du != *last => {}
+            _ => staircase.push((u, du)),
+        }
+    }
+    Ok(staircase)
+}
+
+/// Sorts skyline results by their distance to keyword `dim`, then by node.
+pub fn sort_skyline<N: Ord, D: Ord>(results: &mut [(N, Vec<D>)], dim: usize) {
+    results.sort_unstable_by(|(u, du), (v, dv)| du[dim].cmp(&dv[dim]).then_with(|| u.cmp(v)));
+}
+
+/// Returns the skyline member with the lexicographically smallest distance vector, ties broken by the smaller node,
+/// or `None` if `dists` is empty.
+///
+/// A lexicographically minimal vector cannot be dominated, so this scans `dists` once instead of computing the
+/// skyline first.
+pub fn lexicographic_best<N, D>(dists: &DistanceMap<N, D>) -> Option<(N, Vec<D>)>
+where
+    N: Copy + Ord,
+    D: Clone + Ord,
+{
+    dists
+        .iter()
+        .min_by(|(u, du), (v, dv)| du.cmp(dv).then(u.cmp(v)))
+        .map(|(u, du)| (*u, du.clone()))
+}
+
+/// Returns the indices of the points not dominated by any other point, i.e. the minimal elements of the partially
+/// ordered set.
+///
+/// # Panics
+///
+/// Panics if the points do not all have the same length.
+pub fn skyline<D: Ord, P: AsRef<[D]>>(points: &[P]) -> Vec<usize> {
+    // with a single keyword the skyline is just the points at minimum distance
+    if points
+        .first()
+        .is_some_and(|point| point.as_ref().len() == 1)
+    {
+        fn column<D, P: AsRef<[D]>>(point: &P) -> &D {
+            match point.as_ref() {
+                [dist] => dist,
+                point => panic!("point of length {} among points of length 1", point.len()),
+            }
+        }
+        let min = points.iter().map(column).min().unwrap();
+        return (0..points.len())
+            .filter(|&u| column(&points[u]) == min)
+            .collect();
+    }
+    (0..points.len())
+        .filter(|&u| {
+            points
+                .iter()
+                .all(|dv| partial_cmp(dv.as_ref(), points[u].as_ref()) != Some(Ordering::Less))
+        })
+        .collect()
+}
+
+/// Computes the skyline of precomputed distances by running [`skyline`] over the distance vectors of `dists` and
+/// mapping the result back to nodes.
+pub fn minimal_elements<N: Copy, D: Clone + Ord>(dists: &DistanceMap<N, D>) -> Vec<(N, Vec<D>)> {
+    let (nodes, points): (Vec<_>, Vec<_>) = dists.iter().map(|(u, du)| (*u, du)).unzip();
+    skyline(&points)
+        .into_iter()
+        .map(|idx| (nodes[idx], points[idx].clone()))
+        .collect()
+}
+
+/// Lazy skyline of a [`DistanceMap`], checking one node per call to `next`. Stopping after the first few members
+/// saves the dominance checks of the remaining nodes; the keyword distances themselves are computed upfront.
+#[derive(Clone, Debug)]
+pub struct SkylineIter<N, D> {
+    nodes: Vec<N>,
+    points: Vec<Vec<D>>,
+    next: usize,
+}
+
+impl<N, D> SkylineIter<N, D> {
+    /// Iterates over the skyline of `dists`, in the iteration order of `dists`.
+    pub fn new(dists: DistanceMap<N, D>) -> Self {
+        let (nodes, points) = dists.into_iter().unzip();
+        SkylineIter {
+            nodes,
+            points,
+            next: 0,
+        }
+    }
+}
+
+impl<N: Copy, D: Clone + Ord> Iterator for SkylineIter<N, D> {
+    type Item = (N, Vec<D>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next < self.nodes.len() {
+            let u = self.next;
+            self.next += 1;
+            let dominated = self
+                .points
+                .iter()
+                .any(|dv| partial_cmp(dv, &self.points[u]) == Some(Ordering::Less));
+            if !dominated {
+                return Some((self.nodes[u], self.points[u].clone()));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.nodes.len() - self.next))
+    }
+}
+
+/// Like [`semantic_place_skyline`], but returns the skyline lazily, see [`SkylineIter`].
+pub fn semantic_place_skyline_iter<G, K, D>(
     graph: G,
     node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
     keywords: &[K],
-) -> Vec<(G::NodeId, Vec<D>)>
+) -> Result<SkylineIter<G::NodeId, D>, SkylineError>
 where
     G: IntoNodeIdentifiers + IntoNeighborsDirected,
-    G::NodeId: Hash + Ord,
+    G::NodeId: Hash + Eq,
     K: Ord,
     D: Copy + Ord + Zero + One + UpperBounded,
 {
     // at least one keyword should be provided
-    assert!(!keywords.is_empty());
-    // initialize keyword distances
-    let mut dists: HashMap<_, _> = graph
-        .node_identifiers()
-        .map(|node| (node, vec![D::max_value(); keywords.len()]))
-        .collect();
-    // for each keyword, calculate the distance from each node to the nodes containing it
-    // implemented by multi-source bfs
-    for (keyword_idx, keyword) in keywords.iter().enumerate() {
-        let mut queue: VecDeque<_> = graph
-            .node_identifiers()
-            .filter(|node| {
-                node_to_keyword
-                    .get(node)
-                    .is_some_and(|node_keywords| node_keywords.binary_search(keyword).is_ok())
-            })
-            .collect();
-        for node in &queue {
-            *dists.get_mut(node).unwrap().get_mut(keyword_idx).unwrap() = D::zero();
-        }
-        while let Some(current) = queue.pop_front() {
-            let dist = dists[&current][keyword_idx];
-            for nbr in graph.neighbors_directed(current, Direction::Incoming) {
-                let nbr_dist = dists
-                    .get_mut(&nbr)
-                    .and_then(|v| v.get_mut(keyword_idx))
-                    .unwrap();
-                if dist + D::one() < *nbr_dist {
-                    *nbr_dist = dist + D::one();
-                    queue.push_back(nbr);
-                }
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    Ok(SkylineIter::new(keyword_distances(
+        graph,
+        node_to_keyword,
+        keywords,
+    )))
+}
+
+/// Returns the skyline nodes of `dists` dominating `node`, i.e. the reasons why `node` is not part of the skyline.
+/// Skyline nodes and nodes absent from `dists` have no dominators.
+pub fn dominators_of<N, D>(dists: &DistanceMap<N, D>, node: N) -> Vec<N>
+where
+    N: Copy + Hash + Eq,
+    D: Clone + Ord,
+{
+    let Some(dv) = dists.get(&node) else {
+        return Vec::new();
+    };
+    minimal_elements(dists)
+        .into_iter()
+        .filter(|(_, du)| partial_cmp(du, dv) == Some(Ordering::Less))
+        .map(|(u, _)| u)
+        .collect()
+}
+
+/// Distance vector ordered by dominance: `a < b` iff `a` dominates `b`.
+///
+/// This is only a partial order: `partial_cmp` returns `None` for incomparable vectors and for vectors of different
+/// lengths, so `DistVec` must not be used where a total order is expected, e.g. in `sort` or as a `BTreeSet` key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DistVec<D>(pub Vec<D>);
+
+impl<D: Ord> PartialOrd for DistVec<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        dominance(&self.0, &other.0).ok().flatten()
+    }
+}
+
+/// Error returned when comparing distance vectors of different lengths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DimensionMismatch {
+    /// Length of the first vector.
+    pub left: usize,
+    /// Length of the second vector.
+    pub right: usize,
+}
+
+impl fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot compare distance vectors of lengths {} and {}",
+            self.left, self.right
+        )
+    }
+}
+
+impl Error for DimensionMismatch {}
+
+/// Error returned by the query functions. Only [`semantic_place_skyline`] panics on an empty keyword list instead,
+/// and has [`try_semantic_place_skyline`] as its fallible form; the remaining panics are for malformed arguments
+/// and are documented per function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkylineError {
+    /// The [`estimated_work`] of the query exceeds the given budget.
+    BudgetExceeded { estimated: u64, budget: u64 },
+    /// The query was cancelled before it completed.
+    Cancelled,
+    /// The function supports exactly `expected` keywords, but `found` were queried.
+    KeywordCount { expected: usize, found: usize },
+    /// No keyword was queried.
+    EmptyKeywords,
+    /// Queried keyword `keyword_idx` occurs in no node.
+    KeywordNotFound { keyword_idx: usize },
+    /// Queried keyword `keyword_idx` is unreachable from every node, see [`validate_distances`].
+    UnreachableKeyword { keyword_idx: usize },
+    /// `count` nodes with keywords are not part of the graph.
+    UnknownNodes { count: usize },
+    /// The graph has `count` nodes, too many to number with `u32` indices.
+    TooManyNodes { count: usize },
+    /// Queried keyword `keyword_idx` is not part of the [`KeywordDistanceIndex`].
+    KeywordNotIndexed { keyword_idx: usize },
+    /// A [`TraversalCost`] multiplier does not convert to a `u32` hop count.
+    CostOutOfRange,
+}
+
+impl fmt::Display for SkylineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkylineError::BudgetExceeded { estimated, budget } => write!(
+                f,
+                "estimated work {} exceeds the budget of {}",
+                estimated, budget
+            ),
+            SkylineError::Cancelled => write!(f, "the query was cancelled"),
+            SkylineError::KeywordCount { expected, found } => write!(
+                f,
+                "expected {} keywords, but {} were queried",
+                expected, found
+            ),
+            SkylineError::EmptyKeywords => write!(f, "at least one keyword should be provided"),
+            SkylineError::KeywordNotFound { keyword_idx } => {
+                write!(f, "keyword {} occurs in no node", keyword_idx)
+            }
+            SkylineError::UnreachableKeyword { keyword_idx } => {
+                write!(f, "keyword {} is unreachable from every node", keyword_idx)
+            }
+            SkylineError::UnknownNodes { count } => {
+                write!(f, "{} nodes with keywords are not part of the graph", count)
+            }
+            SkylineError::TooManyNodes { count } => {
+                write!(f, "{} nodes do not fit u32 indices", count)
+            }
+            SkylineError::KeywordNotIndexed { keyword_idx } => {
+                write!(f, "keyword {} is not indexed", keyword_idx)
+            }
+            SkylineError::CostOutOfRange => {
+                write!(f, "traversal cost multipliers should be u32 hop counts")
             }
         }
     }
-    // find the minimal elements in the partially ordered set
-    dists
-        .iter()
-        .filter(|(_, du)| {
-            dists
-                .values()
-                .all(|dv| partial_cmp(dv, du) != Some(Ordering::Less))
-        })
-        .map(|(u, du)| (*u, du.clone()))
-        .collect()
+}
+
+impl Error for SkylineError {}
+
+/// Compares two distance vectors by dominance: `Less` if `dv1` dominates `dv2`, `Greater` if `dv2` dominates `dv1`,
+/// `Equal` if they are equal and `None` if they are incomparable.
+pub fn dominance<D: Ord>(dv1: &[D], dv2: &[D]) -> Result<Option<Ordering>, DimensionMismatch> {
+    if dv1.len() != dv2.len() {
+        return Err(DimensionMismatch {
+            left: dv1.len(),
+            right: dv2.len(),
+        });
+    }
+    Ok(partial_cmp(dv1, dv2))
 }
 
 fn partial_cmp<D: Ord>(dv1: &[D], dv2: &[D]) -> Option<Ordering> {
@@ -89,3 +3019,1068 @@ fn partial_cmp<D: Ord>(dv1: &[D], dv2: &[D]) -> Option<Ordering> {
         Ordering::Equal => partial_cmp(dv1, dv2),
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use petgraph::algo::dijkstra;
+    use petgraph::visit::Reversed;
+
+    pub(crate) type Graph = DiGraphMap<u32, ()>;
+    pub(crate) type NodeToKeyword = HashMap<u32, Vec<u32>>;
+
+    /// Deterministic xorshift generator for the randomized tests.
+    pub(crate) struct XorShift(u64);
+
+    impl XorShift {
+        pub(crate) fn new(seed: u64) -> Self {
+            XorShift(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+        }
+
+        /// Uniform value in `0..n`.
+        pub(crate) fn below(&mut self, n: u32) -> u32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as u32
+        }
+    }
+
+    pub(crate) fn keywords(entries: &[(u32, &[u32])]) -> NodeToKeyword {
+        entries
+            .iter()
+            .map(|&(node, node_keywords)| (node, node_keywords.to_vec()))
+            .collect()
+    }
+
+    /// Random graph over nodes `0..num_nodes` where about a third of the nodes hold one or two of the keywords
+    /// `0..num_keywords`.
+    pub(crate) fn random_graph(
+        rng: &mut XorShift,
+        num_nodes: u32,
+        num_edges: u32,
+        num_keywords: u32,
+    ) -> (Graph, NodeToKeyword) {
+        let mut graph = Graph::new();
+        for node in 0..num_nodes {
+            graph.add_node(node);
+        }
+        for _ in 0..num_edges {
+            graph.add_edge(rng.below(num_nodes), rng.below(num_nodes), ());
+        }
+        let mut node_to_keyword = NodeToKeyword::new();
+        for node in 0..num_nodes {
+            if rng.below(3) == 0 {
+                let mut node_keywords = vec![rng.below(num_keywords), rng.below(num_keywords)];
+                node_keywords.truncate(1 + rng.below(2) as usize);
+                node_keywords.sort_unstable();
+                node_keywords.dedup();
+                node_to_keyword.insert(node, node_keywords);
+            }
+        }
+        (graph, node_to_keyword)
+    }
+
+    /// Distances from each node to the keyword nodes of each column by one Dijkstra per keyword node, independent of
+    /// the searches under test.
+    pub(crate) fn reference_distances(
+        graph: &Graph,
+        node_to_keyword: &NodeToKeyword,
+        keywords: &[u32],
+    ) -> DistanceMap<u32, u32> {
+        let mut dists: DistanceMap<_, _> = graph
+            .nodes()
+            .map(|node| (node, vec![u32::MAX; keywords.len()]))
+            .collect();
+        for (&source, source_keywords) in node_to_keyword {
+            let from_source = dijkstra(Reversed(graph), source, None, |_| 1u32);
+            for (keyword_idx, keyword) in keywords.iter().enumerate() {
+                if source_keywords.binary_search(keyword).is_ok() {
+                    for (node, &dist) in &from_source {
+                        let d = &mut dists.get_mut(node).unwrap()[keyword_idx];
+                        *d = (*d).min(dist);
+                    }
+                }
+            }
+        }
+        dists
+    }
+
+    pub(crate) fn sorted<D: Ord>(mut skyline: Skyline<u32, D>) -> Skyline<u32, D> {
+        skyline.sort_unstable();
+        skyline
+    }
+
+    #[test]
+    fn queries_reject_empty_keywords() {
+        let graph = Graph::from_edges([(0, 1)]);
+        let node_to_keyword = keywords(&[(1, &[7])]);
+        let none: &[u32] = &[];
+        let err = Err(SkylineError::EmptyKeywords);
+        assert_eq!(
+            semantic_place_skyline_within(&graph, &node_to_keyword, none, 3u32),
+            err
+        );
+        assert_eq!(
+            semantic_place_skyline_bounded::<_, _, u32>(&graph, &node_to_keyword, none, &[]),
+            err
+        );
+        assert_eq!(
+            semantic_place_skyline_with_mode::<_, _, u32>(
+                &graph,
+                &node_to_keyword,
+                none,
+                Mode::Undirected
+            ),
+            err
+        );
+        assert_eq!(
+            semantic_place_skyline_from_seeds::<_, _, u32>(&graph, &node_to_keyword, none, &[0]),
+            err
+        );
+        assert_eq!(
+            semantic_place_skyline_excluding::<_, _, u32>(
+                &graph,
+                &node_to_keyword,
+                none,
+                &HashSet::new()
+            ),
+            err
+        );
+        assert_eq!(
+            semantic_place_skyline_among::<_, _, u32, _>(&graph, &node_to_keyword, none, |_| true),
+            err
+        );
+        assert_eq!(
+            semantic_place_skyline_with_anchor::<_, _, u32>(
+                &graph,
+                &node_to_keyword,
+                none,
+                None,
+                false
+            ),
+            Err(SkylineError::EmptyKeywords)
+        );
+        assert_eq!(
+            semantic_place_skyline_with_sentinel(&graph, &node_to_keyword, none, 0u32),
+            err
+        );
+        assert_eq!(
+            approximate_skyline::<_, _, u32>(&graph, &node_to_keyword, none, 1.0, 0),
+            err
+        );
+        assert_eq!(
+            semantic_place_skyline_csr::<_, _, u32>(
+                &CsrGraph::from_digraphmap(&graph).unwrap(),
+                &node_to_keyword,
+                none
+            ),
+            err
+        );
+        assert_eq!(
+            semantic_place_skyline_flat::<_, _, u32>(&graph, &node_to_keyword, none),
+            err
+        );
+        assert_eq!(
+            try_semantic_place_skyline::<_, _, u32>(&graph, &node_to_keyword, none),
+            err
+        );
+        let cost = TraversalCost {
+            forward: 1u32,
+            reverse: 2,
+        };
+        assert_eq!(
+            semantic_place_skyline_with_traversal_cost(
+                &graph,
+                &node_to_keyword,
+                none,
+                |_, _| 1,
+                cost
+            ),
+            err
+        );
+        assert_eq!(
+            semantic_place_skyline_ranked::<_, _, u32>(
+                &graph,
+                &node_to_keyword,
+                none,
+                Aggregate::Sum
+            ),
+            Err(SkylineError::EmptyKeywords)
+        );
+        assert_eq!(
+            best_skyline_place::<_, _, u32>(&graph, &node_to_keyword, none, Aggregate::Sum),
+            Err(SkylineError::EmptyKeywords)
+        );
+        assert_eq!(
+            SkylineEngine::new(graph.clone(), node_to_keyword.clone()).query::<u32>(none),
+            err
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one keyword should be provided")]
+    fn semantic_place_skyline_panics_on_empty_keywords() {
+        let graph = Graph::from_edges([(0, 1)]);
+        semantic_place_skyline::<_, _, u32>(&graph, &keywords(&[(1, &[7])]), &[]);
+    }
+
+    #[test]
+    fn combined_distances_match_keyword_distances() {
+        let mut rng = XorShift::new(178);
+        for _ in 0..20 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 50, 120, 3);
+            // keyword 5 occurs in no node
+            for query in [&[0][..], &[0, 1, 2], &[2, 0, 5]] {
+                assert_eq!(
+                    keyword_distances_combined::<_, _, u32>(&graph, &node_to_keyword, query),
+                    keyword_distances(&graph, &node_to_keyword, query)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn single_keyword_skyline_matches_dominance_filter() {
+        let mut rng = XorShift::new(179);
+        for num_points in 1..40 {
+            let points: Vec<_> = (0..num_points).map(|_| vec![rng.below(5)]).collect();
+            let expected: Vec<_> = (0..points.len())
+                .filter(|&u| {
+                    points
+                        .iter()
+                        .all(|dv| partial_cmp(dv, &points[u]) != Some(Ordering::Less))
+                })
+                .collect();
+            assert_eq!(skyline(&points), expected);
+        }
+    }
+
+    #[test]
+    fn cancellable_matches_uncancelled_query() {
+        let mut rng = XorShift::new(1);
+        let (graph, node_to_keyword) = random_graph(&mut rng, 60, 150, 4);
+        let result = semantic_place_skyline_cancellable::<_, _, u32>(
+            &graph,
+            &node_to_keyword,
+            &[0, 1, 2],
+            &AtomicBool::new(false),
+            false,
+        )
+        .unwrap();
+        assert!(result.complete);
+        assert_eq!(
+            sorted(result.skyline),
+            sorted(semantic_place_skyline(&graph, &node_to_keyword, &[0, 1, 2]))
+        );
+    }
+
+    #[test]
+    fn cancelled_query_fails_or_returns_partial_skyline() {
+        let graph = Graph::from_edges([(0, 1), (1, 2)]);
+        let node_to_keyword = keywords(&[(2, &[7])]);
+        let cancel = AtomicBool::new(true);
+        let result = semantic_place_skyline_cancellable::<_, _, u32>(
+            &graph,
+            &node_to_keyword,
+            &[7],
+            &cancel,
+            false,
+        );
+        assert_eq!(result, Err(SkylineError::Cancelled));
+        let result = semantic_place_skyline_cancellable::<_, _, u32>(
+            &graph,
+            &node_to_keyword,
+            &[7],
+            &cancel,
+            true,
+        )
+        .unwrap();
+        assert!(!result.complete);
+        // nothing was searched, so every node is equally unreachable
+        assert_eq!(result.skyline.len(), 3);
+    }
+
+    #[test]
+    fn nearest_source_ties_go_to_smallest_node() {
+        // node 0 is one hop from both keyword nodes, whichever edge is iterated first
+        for edges in [[(0, 1), (0, 2)], [(0, 2), (0, 1)]] {
+            let graph = Graph::from_edges(edges);
+            let node_to_keyword = keywords(&[(1, &[7]), (2, &[7])]);
+            let dists = keyword_distances_with_sources::<_, _, u32>(&graph, &node_to_keyword, &[7]);
+            assert_eq!(dists[&0], vec![(1, Some(1))]);
+            assert_eq!(dists[&2], vec![(0, Some(2))]);
+        }
+    }
+
+    #[test]
+    fn nearest_sources_match_reference_distances() {
+        let mut rng = XorShift::new(2);
+        for _ in 0..20 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 40, 80, 3);
+            let keywords = [0, 1, 2];
+            let expected = reference_distances(&graph, &node_to_keyword, &keywords);
+            let dists =
+                keyword_distances_with_sources::<_, _, u32>(&graph, &node_to_keyword, &keywords);
+            for (node, node_dists) in &dists {
+                for (keyword_idx, &(dist, source)) in node_dists.iter().enumerate() {
+                    assert_eq!(dist, expected[node][keyword_idx]);
+                    // the source holds the keyword and lies at the reported distance
+                    if let Some(source) = source {
+                        assert!(node_to_keyword[&source].contains(&keywords[keyword_idx]));
+                        let from_source = dijkstra(Reversed(&graph), source, Some(*node), |_| 1u32);
+                        assert_eq!(from_source[node], dist);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn multiplicity_uses_distance_to_nth_keyword_node() {
+        // keyword 7 at nodes 1 and 3 of the path 0 -> 1 -> 2 -> 3
+        let graph = Graph::from_edges([(0, 1), (1, 2), (2, 3)]);
+        let node_to_keyword = keywords(&[(1, &[7]), (3, &[7])]);
+        let result = semantic_place_skyline_with_multiplicity::<_, _, u32>(
+            &graph,
+            &node_to_keyword,
+            &[7],
+            &[2],
+            &[u32::MAX],
+        )
+        .unwrap();
+        assert_eq!(result, vec![(1, vec![2])]);
+        // beyond the cutoff no node has two keyword nodes
+        let result = semantic_place_skyline_with_multiplicity::<_, _, u32>(
+            &graph,
+            &node_to_keyword,
+            &[7],
+            &[2],
+            &[1],
+        )
+        .unwrap();
+        assert_eq!(result.len(), 4);
+        assert!(result.iter().all(|(_, dist)| dist == &[u32::MAX]));
+        // with a single instance of the keyword its column is unreachable everywhere
+        let result = semantic_place_skyline_with_multiplicity::<_, _, u32>(
+            &graph,
+            &keywords(&[(3, &[7])]),
+            &[7],
+            &[2],
+            &[u32::MAX],
+        )
+        .unwrap();
+        assert_eq!(result.len(), 4);
+        assert!(result.iter().all(|(_, dist)| dist == &[u32::MAX]));
+    }
+
+    #[test]
+    fn multiplicity_matches_brute_force() {
+        let mut rng = XorShift::new(3);
+        for _ in 0..20 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 30, 70, 2);
+            let (counts, max_dist) = ([2, 3], [4, u32::MAX]);
+            // distance to the counts[i]-th nearest keyword node within max_dist[i]
+            let mut expected: DistanceMap<_, _> = HashMap::default();
+            for node in graph.nodes() {
+                let from_node = dijkstra(&graph, node, None, |_| 1u32);
+                let column = |keyword_idx: usize| {
+                    let mut found: Vec<_> = from_node
+                        .iter()
+                        .filter(|(other, &dist)| {
+                            dist <= max_dist[keyword_idx]
+                                && node_to_keyword
+                                    .get(other)
+                                    .is_some_and(|k| k.contains(&(keyword_idx as u32)))
+                        })
+                        .map(|(_, &dist)| dist)
+                        .collect();
+                    found.sort_unstable();
+                    found
+                        .get(counts[keyword_idx] - 1)
+                        .copied()
+                        .unwrap_or(u32::MAX)
+                };
+                expected.insert(node, vec![column(0), column(1)]);
+            }
+            let result = semantic_place_skyline_with_multiplicity(
+                &graph,
+                &node_to_keyword,
+                &[0, 1],
+                &counts,
+                &max_dist,
+            )
+            .unwrap();
+            assert_eq!(sorted(result), sorted(minimal_elements(&expected)));
+        }
+    }
+
+    #[test]
+    fn csr_matches_graph_map() {
+        let mut rng = XorShift::new(4);
+        for _ in 0..20 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 50, 120, 3);
+            let csr = CsrGraph::from_digraphmap(&graph).unwrap();
+            assert_eq!(
+                sorted(
+                    semantic_place_skyline_csr::<_, _, u32>(&csr, &node_to_keyword, &[0, 2])
+                        .unwrap()
+                ),
+                sorted(semantic_place_skyline(&graph, &node_to_keyword, &[0, 2]))
+            );
+        }
+    }
+
+    #[test]
+    fn keyword_distances_match_reference() {
+        let mut rng = XorShift::new(5);
+        for _ in 0..20 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 50, 100, 3);
+            assert_eq!(
+                keyword_distances(&graph, &node_to_keyword, &[0, 1, 2]),
+                reference_distances(&graph, &node_to_keyword, &[0, 1, 2])
+            );
+        }
+    }
+
+    #[test]
+    fn keyword_distances_with_matches_graph_adapter() {
+        let mut rng = XorShift::new(6);
+        let (graph, node_to_keyword) = random_graph(&mut rng, 50, 120, 3);
+        let keywords = [0, 2];
+        let dists = keyword_distances_with(
+            graph.nodes(),
+            |node| graph.neighbors_directed(node, Direction::Incoming),
+            |node, keyword_idx| {
+                node_to_keyword
+                    .get(&node)
+                    .is_some_and(|k| k.contains(&keywords[keyword_idx]))
+            },
+            keywords.len(),
+        );
+        assert_eq!(
+            dists,
+            keyword_distances::<_, _, u32>(&graph, &node_to_keyword, &keywords)
+        );
+    }
+
+    #[test]
+    fn candidate_queries_match_unbounded_computation() {
+        let mut rng = XorShift::new(7);
+        for _ in 0..30 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 50, 70, 3);
+            let keywords = [0, 1, 2];
+            let dists = keyword_distances::<_, _, u32>(&graph, &node_to_keyword, &keywords);
+            let restricted = |is_candidate: &dyn Fn(u32) -> bool| {
+                let mut dists = dists.clone();
+                dists.retain(|&node, _| is_candidate(node));
+                sorted(minimal_elements(&dists))
+            };
+            let modulus = 2 + rng.below(8);
+            let is_candidate = |node: u32| node.is_multiple_of(modulus);
+            assert_eq!(
+                sorted(
+                    semantic_place_skyline_among(&graph, &node_to_keyword, &keywords, is_candidate)
+                        .unwrap()
+                ),
+                restricted(&is_candidate)
+            );
+            let seeds = [rng.below(50), rng.below(50)];
+            let mut reachable = HashSet::new();
+            for seed in seeds {
+                let mut bfs = petgraph::visit::Bfs::new(&graph, seed);
+                while let Some(node) = bfs.next(&graph) {
+                    reachable.insert(node);
+                }
+            }
+            assert_eq!(
+                sorted(
+                    semantic_place_skyline_from_seeds(&graph, &node_to_keyword, &keywords, &seeds)
+                        .unwrap()
+                ),
+                restricted(&|node| reachable.contains(&node))
+            );
+        }
+    }
+
+    #[test]
+    fn sentinel_marks_unreachable_keywords() {
+        // keyword 7 is reachable from 0 and 1, keyword 8 only from the isolated node 2
+        let mut graph = Graph::from_edges([(0, 1)]);
+        graph.add_node(2);
+        let node_to_keyword = keywords(&[(1, &[7]), (2, &[8])]);
+        let result =
+            semantic_place_skyline_with_sentinel(&graph, &node_to_keyword, &[7, 8], 999u32)
+                .unwrap();
+        assert_eq!(sorted(result), vec![(1, vec![0, 999]), (2, vec![999, 0])]);
+        // a sentinel below the reachable distances, e.g. -1, still ranks behind them
+        let result =
+            semantic_place_skyline_with_sentinel(&graph, &node_to_keyword, &[7], -1i32).unwrap();
+        assert_eq!(result, vec![(1, vec![0])]);
+    }
+
+    #[test]
+    fn sentinel_matches_default_query() {
+        let mut rng = XorShift::new(8);
+        for _ in 0..20 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 50, 60, 3);
+            let expected: Vec<_> =
+                semantic_place_skyline::<_, _, u32>(&graph, &node_to_keyword, &[0, 1])
+                    .into_iter()
+                    .map(|(node, dist)| {
+                        let dist = dist
+                            .into_iter()
+                            .map(|d| if d == u32::MAX { 10_000 } else { d });
+                        (node, dist.collect())
+                    })
+                    .collect();
+            assert_eq!(
+                sorted(
+                    semantic_place_skyline_with_sentinel(&graph, &node_to_keyword, &[0, 1], 10_000)
+                        .unwrap()
+                ),
+                sorted(expected)
+            );
+        }
+    }
+
+    /// Small edge weights derived from the endpoints, for the weighted searches.
+    fn hop_weight(source: u32, target: u32) -> u32 {
+        (source * 7 + target * 13) % 4
+    }
+
+    #[test]
+    fn hop_weighted_matches_dijkstra() {
+        let mut rng = XorShift::new(9);
+        for _ in 0..20 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 50, 120, 3);
+            assert_eq!(
+                sorted(
+                    semantic_place_skyline_hop_weighted::<_, _, u32, _>(
+                        &graph,
+                        &node_to_keyword,
+                        &[0, 1, 2],
+                        hop_weight
+                    )
+                    .unwrap()
+                ),
+                sorted(
+                    semantic_place_skyline_weighted(&graph, &node_to_keyword, &[0, 1, 2], |edge| {
+                        hop_weight(edge.source(), edge.target())
+                    })
+                    .unwrap()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn hop_weighted_distances_saturate() {
+        // node 0 is 400 hops from keyword 7, more than u8 holds
+        let graph = Graph::from_edges([(0, 1), (1, 2)]);
+        let node_to_keyword = keywords(&[(0, &[8]), (2, &[7])]);
+        let result = semantic_place_skyline_hop_weighted::<_, _, u8, _>(
+            &graph,
+            &node_to_keyword,
+            &[7, 8],
+            |_, _| 200,
+        )
+        .unwrap();
+        assert_eq!(
+            sorted(result),
+            vec![(0, vec![u8::MAX, 0]), (2, vec![0, u8::MAX])]
+        );
+    }
+
+    #[test]
+    fn traversal_cost_on_asymmetric_graph() {
+        // 0 -> 1 -> 2, where edge 1 -> 2 weighs two hops; going against an edge costs three times as much
+        let graph = Graph::from_edges([(0, 1), (1, 2)]);
+        let node_to_keyword = keywords(&[(0, &[8]), (2, &[7])]);
+        let hop_weight = |source, target| if (source, target) == (1, 2) { 2 } else { 1 };
+        let cost = TraversalCost {
+            forward: 1u32,
+            reverse: 3,
+        };
+        let result = semantic_place_skyline_with_traversal_cost(
+            &graph,
+            &node_to_keyword,
+            &[7, 8],
+            hop_weight,
+            cost,
+        );
+        // keyword 7 forward: 0 -> 1 -> 2 costs 1 + 2; keyword 8 backward: 2 -> 1 -> 0 costs 3 * 2 + 3 * 1
+        assert_eq!(
+            sorted(result.unwrap()),
+            vec![(0, vec![3, 0]), (1, vec![2, 3]), (2, vec![0, 9])]
+        );
+        let cost = TraversalCost {
+            forward: 1i64,
+            reverse: -1,
+        };
+        assert_eq!(
+            semantic_place_skyline_with_traversal_cost(
+                &graph,
+                &node_to_keyword,
+                &[7],
+                hop_weight,
+                cost
+            ),
+            Err(SkylineError::CostOutOfRange)
+        );
+    }
+
+    #[test]
+    fn distance_map_type_is_fixed() {
+        // the `fast-hash` feature only changes the hasher inside the search, not the returned map
+        let mut rng = XorShift::new(10);
+        let (graph, node_to_keyword) = random_graph(&mut rng, 30, 60, 2);
+        let dists: HashMap<u32, Vec<u32>, RandomState> =
+            keyword_distances(&graph, &node_to_keyword, &[0, 1]);
+        assert_eq!(
+            dists,
+            reference_distances(&graph, &node_to_keyword, &[0, 1])
+        );
+    }
+
+    #[test]
+    fn approximate_skyline_samples_reproducibly() {
+        let graph = Graph::from_edges((0..2_000).map(|node| (node, node + 1)));
+        let node_to_keyword = keywords(&[(2_000, &[7])]);
+        let sample = |seed| {
+            // unreachable nodes are all in the skyline, so the result is the sample itself
+            let result: Vec<(u32, Vec<u32>)> =
+                approximate_skyline(&graph, &node_to_keyword, &[8], 0.25, seed).unwrap();
+            let mut nodes: Vec<_> = result.into_iter().map(|(node, _)| node).collect();
+            nodes.sort_unstable();
+            nodes
+        };
+        let nodes = sample(42);
+        assert_eq!(nodes, sample(42));
+        assert_ne!(nodes, sample(43));
+        assert!(
+            (400..600).contains(&nodes.len()),
+            "sampled {} of 2001 nodes",
+            nodes.len()
+        );
+        // fixed by the hash function of this crate
+        assert_eq!(&nodes[..4], &[3, 9, 14, 16]);
+    }
+
+    #[test]
+    fn bounded_uses_a_cutoff_per_keyword() {
+        // keyword 7 at one end of the path 0 - 1 - 2 - 3 - 4, keyword 8 at the other
+        let graph = Graph::from_edges((0..4).flat_map(|node| [(node, node + 1), (node + 1, node)]));
+        let node_to_keyword = keywords(&[(4, &[7]), (0, &[8])]);
+        let max = u32::MAX;
+        let result =
+            semantic_place_skyline_bounded(&graph, &node_to_keyword, &[7, 8], &[1, 3]).unwrap();
+        assert_eq!(
+            sorted(result),
+            vec![(0, vec![max, 0]), (3, vec![1, 3]), (4, vec![0, max])]
+        );
+        let result =
+            semantic_place_skyline_bounded(&graph, &node_to_keyword, &[7, 8], &[3, 1]).unwrap();
+        assert_eq!(
+            sorted(result),
+            vec![(0, vec![max, 0]), (1, vec![3, 1]), (4, vec![0, max])]
+        );
+    }
+
+    #[test]
+    fn empty_graph_yields_empty_skyline() {
+        let graph = Graph::new();
+        let node_to_keyword = NodeToKeyword::new();
+        assert!(keyword_distances::<_, _, u32>(&graph, &node_to_keyword, &[7]).is_empty());
+        assert!(semantic_place_skyline::<_, _, u32>(&graph, &node_to_keyword, &[7, 8]).is_empty());
+        let csr = CsrGraph::from_digraphmap(&graph).unwrap();
+        assert_eq!(
+            semantic_place_skyline_csr::<_, _, u32>(&csr, &node_to_keyword, &[7]),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn single_seed_restricts_candidates_to_its_component() {
+        // two components: 0 -> 1 -> 2 and 3 -> 4
+        let graph = Graph::from_edges([(0, 1), (1, 2), (3, 4)]);
+        let node_to_keyword = keywords(&[(2, &[7]), (3, &[8]), (4, &[7])]);
+        let max = u32::MAX;
+        let result =
+            semantic_place_skyline_from_seeds(&graph, &node_to_keyword, &[7, 8], &[0]).unwrap();
+        assert_eq!(result, vec![(2, vec![0, max])]);
+        // without the seed the other component takes part
+        let result = semantic_place_skyline::<_, _, u32>(&graph, &node_to_keyword, &[7, 8]);
+        assert_eq!(
+            sorted(result),
+            vec![(2, vec![0, max]), (3, vec![1, 0]), (4, vec![0, max])]
+        );
+    }
+
+    #[test]
+    fn dominance_rejects_mismatched_lengths() {
+        assert_eq!(
+            dominance(&[1, 2], &[1]),
+            Err(DimensionMismatch { left: 2, right: 1 })
+        );
+        assert_eq!(
+            dominance::<u32>(&[], &[3]),
+            Err(DimensionMismatch { left: 0, right: 1 })
+        );
+        assert_eq!(dominance(&[1, 2], &[2, 2]), Ok(Some(Ordering::Less)));
+        assert_eq!(dominance(&[1, 3], &[2, 2]), Ok(None));
+    }
+
+    #[test]
+    fn sort_skyline_orders_by_dimension_then_node() {
+        let mut results = vec![
+            (1, vec![2, 0]),
+            (4, vec![1, 1]),
+            (2, vec![0, 2]),
+            (3, vec![1, 1]),
+        ];
+        sort_skyline(&mut results, 0);
+        let nodes: Vec<_> = results.iter().map(|&(node, _)| node).collect();
+        assert_eq!(nodes, [2, 3, 4, 1]);
+        sort_skyline(&mut results, 1);
+        let nodes: Vec<_> = results.iter().map(|&(node, _)| node).collect();
+        assert_eq!(nodes, [1, 3, 4, 2]);
+    }
+
+    #[test]
+    fn mode_changes_distances_on_directed_graph() {
+        // keyword 7 at the head of the path 0 -> 1 -> 2, keyword 8 at its tail
+        let graph = Graph::from_edges([(0, 1), (1, 2)]);
+        let node_to_keyword = keywords(&[(2, &[7]), (0, &[8])]);
+        let max = u32::MAX;
+        let query = |mode| {
+            sorted(
+                semantic_place_skyline_with_mode(&graph, &node_to_keyword, &[7, 8], mode).unwrap(),
+            )
+        };
+        assert_eq!(
+            query(Mode::NodeToKeyword),
+            sorted(semantic_place_skyline(&graph, &node_to_keyword, &[7, 8]))
+        );
+        assert_eq!(
+            query(Mode::NodeToKeyword),
+            vec![(0, vec![2, 0]), (2, vec![0, max])]
+        );
+        assert_eq!(
+            query(Mode::KeywordToNode),
+            vec![(0, vec![max, 0]), (2, vec![0, 2])]
+        );
+        assert_eq!(
+            query(Mode::Undirected),
+            vec![(0, vec![2, 0]), (1, vec![1, 1]), (2, vec![0, 2])]
+        );
+    }
+
+    #[test]
+    fn coverage_ratio_counts_reachable_keywords() {
+        // node 3 reaches both keywords, nodes 0 to 2 one of them, the isolated node 4 none
+        let mut graph = Graph::from_edges([(0, 1), (3, 1), (3, 2)]);
+        graph.add_node(4);
+        let node_to_keyword = keywords(&[(1, &[7]), (2, &[8])]);
+        let dists = keyword_distances::<_, _, u32>(&graph, &node_to_keyword, &[7, 8]);
+        let ratios = coverage_ratio(&dists);
+        assert_eq!(
+            ratios,
+            HashMap::from([(0, 0.5), (1, 0.5), (2, 0.5), (3, 1.0), (4, 0.0)])
+        );
+        assert_eq!(coverage(&dists), [3, 2]);
+    }
+
+    #[test]
+    fn anchor_gates_candidates() {
+        // node 2 only reaches keyword 8, nodes 0 and 1 only keyword 7
+        let mut graph = Graph::from_edges([(0, 1)]);
+        graph.add_node(2);
+        let node_to_keyword = keywords(&[(1, &[7]), (2, &[8])]);
+        let max = u32::MAX;
+        let query = |anchor| {
+            sorted(
+                semantic_place_skyline_with_anchor(
+                    &graph,
+                    &node_to_keyword,
+                    &[7, 8],
+                    anchor,
+                    false,
+                )
+                .unwrap()
+                .skyline,
+            )
+        };
+        assert_eq!(query(None), vec![(1, vec![0, max]), (2, vec![max, 0])]);
+        assert_eq!(query(Some(0)), vec![(1, vec![0, max])]);
+        assert_eq!(query(Some(1)), vec![(2, vec![max, 0])]);
+    }
+
+    #[test]
+    fn seeds_skip_keyword_components_out_of_reach() {
+        // keyword 8 only occurs in the component without a seed
+        let graph = Graph::from_edges([(0, 1), (2, 3)]);
+        let node_to_keyword = keywords(&[(1, &[7]), (3, &[7, 8])]);
+        let result =
+            semantic_place_skyline_from_seeds(&graph, &node_to_keyword, &[7, 8], &[0]).unwrap();
+        assert_eq!(result, vec![(1, vec![0, u32::MAX])]);
+        // sparse random graphs have many components
+        let mut rng = XorShift::new(161);
+        for _ in 0..30 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 60, 25, 3);
+            let seeds = [rng.below(60)];
+            let reachable = reachable_from(&graph, &seeds);
+            let mut dists = keyword_distances::<_, _, u32>(&graph, &node_to_keyword, &[0, 1, 2]);
+            dists.retain(|node, _| reachable.contains(node));
+            assert_eq!(
+                sorted(
+                    semantic_place_skyline_from_seeds(&graph, &node_to_keyword, &[0, 1, 2], &seeds)
+                        .unwrap()
+                ),
+                sorted(minimal_elements(&dists))
+            );
+        }
+    }
+
+    #[test]
+    fn excluded_nodes_are_no_candidates() {
+        let graph = Graph::from_edges([(0, 1), (1, 2)]);
+        let node_to_keyword = keywords(&[(2, &[7])]);
+        let query = |exclude: &[u32]| {
+            semantic_place_skyline_excluding::<_, _, u32>(
+                &graph,
+                &node_to_keyword,
+                &[7],
+                &exclude.iter().copied().collect(),
+            )
+            .unwrap()
+        };
+        assert_eq!(query(&[]), vec![(2, vec![0])]);
+        // the excluded keyword node still counts for the distances of the others
+        assert_eq!(query(&[2]), vec![(1, vec![1])]);
+        assert_eq!(query(&[1, 2]), vec![(0, vec![2])]);
+    }
+
+    #[test]
+    fn ranking_paths_accept_weighted_l2() {
+        let graph = Graph::from_edges([(0, 1), (1, 0), (1, 2), (2, 1)]);
+        let node_to_keyword = keywords(&[(0, &[0]), (2, &[1])]);
+        let agg = Aggregate::WeightedL2 {
+            weights: vec![1.0, 4.0],
+        };
+        let expected = vec![
+            (2, vec![2, 0], 2.0),
+            (1, vec![1, 1], 5f64.sqrt()),
+            (0, vec![0, 2], 4.0),
+        ];
+        let mut ranked = semantic_place_skyline_ranked::<_, _, u32>(
+            &graph,
+            &node_to_keyword,
+            &[0, 1],
+            agg.clone(),
+        );
+        if let Ok(ranked) = &mut ranked {
+            ranked.sort_unstable_by(|(_, _, rank_u), (_, _, rank_v)| rank_u.total_cmp(rank_v));
+        }
+        assert_eq!(ranked, Ok(expected.clone()));
+        let flagged =
+            ranked_with_skyline_flag::<_, _, u32>(&graph, &node_to_keyword, &[0, 1], agg.clone());
+        assert_eq!(
+            flagged,
+            Ok(expected
+                .iter()
+                .map(|(node, dist, rank)| (*node, dist.clone(), *rank, true))
+                .collect())
+        );
+        let dists = keyword_distances(&graph, &node_to_keyword, &[0, 1]);
+        assert_eq!(top_k_by_aggregate(&dists, 2, agg), expected[..2]);
+    }
+
+    #[test]
+    fn weighted_sum_ranks_unreachable_keywords_last() {
+        let graph = Graph::from_edges([(0, 1), (1, 2)]);
+        let node_to_keyword = keywords(&[(0, &[0]), (2, &[1])]);
+        let agg = Aggregate::WeightedSum {
+            weights: vec![3.0, 1.0],
+        };
+        // node 0 reaches both keywords, nodes 1 and 2 only keyword 1
+        let ranked =
+            ranked_with_skyline_flag::<_, _, u32>(&graph, &node_to_keyword, &[0, 1], agg.clone());
+        assert_eq!(
+            ranked,
+            Ok(vec![
+                (0, vec![0, 2], 2.0, true),
+                (1, vec![u32::MAX, 1], f64::INFINITY, false),
+                (2, vec![u32::MAX, 0], f64::INFINITY, true),
+            ])
+        );
+        let dists = keyword_distances(&graph, &node_to_keyword, &[0, 1]);
+        assert_eq!(top_k_by_aggregate(&dists, 1, agg), [(0, vec![0, 2], 2.0)]);
+    }
+
+    #[test]
+    fn empty_skylines_fall_back_to_the_nearest_node() {
+        // node 0 reaches keyword 7 at once and keyword 8 in two hops, the others only keyword 8
+        let graph = Graph::from_edges([(0, 1), (1, 2)]);
+        let node_to_keyword = keywords(&[(0, &[7]), (2, &[8])]);
+        let max = u32::MAX;
+        let constrained = |fallback_nearest| {
+            semantic_place_skyline_constrained(
+                &graph,
+                &node_to_keyword,
+                &[7, 8],
+                &[1, 1],
+                fallback_nearest,
+            )
+        };
+        assert_eq!(
+            constrained(false),
+            Ok(FallbackSkyline {
+                skyline: vec![],
+                fallback: false
+            })
+        );
+        assert_eq!(
+            constrained(true),
+            Ok(FallbackSkyline {
+                skyline: vec![(0, vec![0, 2])],
+                fallback: true
+            })
+        );
+        // keyword 9 occurs nowhere, so every sum is infinite and the fewest unreachable keywords win
+        let anchored = |fallback_nearest| {
+            semantic_place_skyline_with_anchor(
+                &graph,
+                &node_to_keyword,
+                &[7, 8, 9],
+                Some(2),
+                fallback_nearest,
+            )
+        };
+        assert_eq!(
+            anchored(false),
+            Ok(FallbackSkyline {
+                skyline: vec![],
+                fallback: false
+            })
+        );
+        assert_eq!(
+            anchored(true),
+            Ok(FallbackSkyline {
+                skyline: vec![(0, vec![0, 2, max])],
+                fallback: true
+            })
+        );
+        // a non-empty skyline is kept as is
+        assert_eq!(
+            semantic_place_skyline_with_anchor(&graph, &node_to_keyword, &[7, 8], Some(1), true)
+                .map(|result| (sorted(result.skyline), result.fallback)),
+            Ok((vec![(0, vec![0, 2]), (2, vec![max, 0])], false))
+        );
+    }
+
+    #[test]
+    fn validate_distances_reports_unreachable_columns() {
+        // only node 2 holds keyword 8, and no seeded candidate reaches it
+        let graph = Graph::from_edges([(0, 1), (2, 3)]);
+        let node_to_keyword = keywords(&[(1, &[7]), (2, &[8])]);
+        let mut dists = keyword_distances::<_, _, u32>(&graph, &node_to_keyword, &[7, 8]);
+        assert_eq!(validate_distances(&dists), Ok(()));
+        dists.retain(|&node, _| node < 2);
+        assert_eq!(
+            validate_distances(&dists),
+            Err(SkylineError::UnreachableKeyword { keyword_idx: 1 })
+        );
+        dists.clear();
+        assert_eq!(validate_distances(&dists), Ok(()));
+    }
+
+    #[test]
+    fn keyword_counts_respect_each_radius() {
+        // the cycle through node 0 must not count a node twice
+        let graph = Graph::from_edges([(0, 1), (1, 2), (2, 3), (2, 0)]);
+        let node_to_keyword = keywords(&[(1, &[7]), (2, &[7]), (3, &[7, 8])]);
+        let counts = |max_dist: &[u32], node| {
+            count_keywords_within(&graph, &node_to_keyword, &[7, 8], max_dist, node)
+        };
+        assert_eq!(counts(&[2, 1], 0), [2, 0]);
+        assert_eq!(counts(&[2, 1], 1), [3, 0]);
+        assert_eq!(counts(&[2, 2], 1), [3, 1]);
+        assert_eq!(counts(&[0, 0], 3), [1, 1]);
+    }
+
+    #[test]
+    fn relativize_rejects_deltas_beyond_i64() {
+        let results = [(0, vec![3u64, u64::MAX - 1, u64::MAX]), (1, vec![1, 5, 2])];
+        assert_eq!(
+            relativize(&results, &[2, 1, 2]),
+            [
+                (0, vec![Some(1), None, None]),
+                (1, vec![Some(-1), Some(4), Some(0)])
+            ]
+        );
+        // both distances fit an i64, but their difference does not
+        let results = [(0, vec![i64::MAX - 1, 0])];
+        assert_eq!(
+            relativize(&results, &[-1, -1]),
+            [(0, vec![Some(i64::MAX), Some(1)])]
+        );
+        assert_eq!(relativize(&results, &[-2, -2]), [(0, vec![None, Some(2)])]);
+    }
+
+    #[test]
+    fn dag_distances_match_the_search() {
+        let mut rng = XorShift::new(124);
+        for _ in 0..20 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 50, 100, 3);
+            // keep the edges running from smaller to larger nodes, which leaves no cycle
+            let mut dag = Graph::new();
+            for node in graph.nodes() {
+                dag.add_node(node);
+            }
+            for (u, v, _) in graph.all_edges().filter(|&(u, v, _)| u != v) {
+                dag.add_edge(u.min(v), u.max(v), ());
+            }
+            let keywords = [0, 1, 2];
+            let dists = dag_keyword_distances::<_, _, u32>(&dag, &node_to_keyword, &keywords);
+            assert_eq!(
+                dists,
+                Some(reference_distances(&dag, &node_to_keyword, &keywords))
+            );
+        }
+        let cycle = Graph::from_edges([(0, 1), (1, 0)]);
+        let node_to_keyword = keywords(&[(0, &[7])]);
+        assert_eq!(
+            dag_keyword_distances::<_, _, u32>(&cycle, &node_to_keyword, &[7]),
+            None
+        );
+    }
+
+    #[test]
+    fn normalized_ranking_scores_the_normalized_distances() {
+        let mut rng = XorShift::new(105);
+        for _ in 0..10 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 40, 80, 3);
+            let keywords = [0, 1, 2];
+            let mut skyline =
+                semantic_place_skyline_normalized::<_, _, u32>(&graph, &node_to_keyword, &keywords)
+                    .unwrap();
+            let mut ranked = semantic_place_skyline_normalized_ranked::<_, _, u32>(
+                &graph,
+                &node_to_keyword,
+                &keywords,
+                Aggregate::Sum,
+            )
+            .unwrap();
+            skyline.sort_unstable_by_key(|&(node, _)| node);
+            ranked.sort_unstable_by_key(|&(node, _, _)| node);
+            assert_eq!(ranked.len(), skyline.len());
+            for ((node, dist, rank), (skyline_node, skyline_dist)) in ranked.iter().zip(&skyline) {
+                assert_eq!((node, dist), (skyline_node, skyline_dist));
+                assert_eq!(*rank, Aggregate::Sum.score(dist));
+                // every normalized distance lies in [0, 1]
+                assert!(rank.is_infinite() || *rank <= keywords.len() as f64);
+            }
+        }
+    }
+}
@@ -1,107 +1,1487 @@
 #![feature(iterator_try_collect)]
 
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+mod graph_files;
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
-use clap::Parser;
+use clap::{Args as ClapArgs, Parser, Subcommand, ValueEnum};
+use petgraph::algo::is_cyclic_directed;
 use petgraph::prelude::DiGraphMap;
+use skyline::fxhash::FxHasher;
+use skyline::KeywordInterner;
 
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    query: Option<Args>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check the input files for problems without running any query
+    Validate(ValidateArgs),
+}
+
+#[derive(ClapArgs, Debug)]
+struct ValidateArgs {
+    /// Path to the edge file
+    #[arg(short)]
+    edge_file: PathBuf,
+
+    /// Layout of the edge file
+    #[arg(long, value_enum, default_value_t = EdgeFormat::Adjacency)]
+    edge_format: EdgeFormat,
+
+    /// Path to the node keyword file, optional for GraphML and DOT. Repeat to check several files
+    #[arg(short)]
+    node_keyword_file: Vec<PathBuf>,
+
+    /// Accept keyword names such as "coffee" instead of integers in the node keyword files
+    #[arg(long)]
+    named_keywords: bool,
+}
+
+#[derive(ClapArgs, Debug)]
 struct Args {
     /// Path to the edge file
     #[arg(short)]
     edge_file: PathBuf,
 
-    /// Path to the node keyword file
+    /// Layout of the edge file
+    #[arg(long, value_enum, default_value_t = EdgeFormat::Adjacency)]
+    edge_format: EdgeFormat,
+
+    /// Path to the node keyword file, optional for GraphML and DOT. Repeat to merge the keywords of several files
     #[arg(short)]
-    node_keyword_file: PathBuf,
+    node_keyword_file: Vec<PathBuf>,
+
+    /// Read keywords as names, e.g. "coffee,park,atm", in the node keyword files and the queries instead of integers
+    #[arg(long)]
+    named_keywords: bool,
+
+    /// Fail instead of warning when a query keyword occurs in no node
+    #[arg(long)]
+    strict: bool,
+
+    /// Report whether the graph is acyclic
+    #[arg(long)]
+    check_dag: bool,
+
+    /// Report the memory before and after building the graph, the peak memory of the build and the estimated memory
+    /// used by the distance matrix of each query
+    #[arg(long)]
+    mem: bool,
+
+    /// Check the input and the queries and print the estimated cost of each query without running any
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Read the input files twice to allocate the graph and the keyword maps at their final size, lowering the peak
+    /// memory of the build. Compare the --mem reports of a build with and without it
+    #[arg(long)]
+    two_pass_build: bool,
+
+    /// Print only the results and explicitly requested reports
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print extra diagnostics such as graph statistics and keyword source counts
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Output format of the results
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Also write the results of all queries to a file, as FORMAT:PATH. Repeat to add several files
+    #[arg(long, value_name = "FORMAT:PATH", value_parser = parse_sink)]
+    sink: Vec<(Format, PathBuf)>,
+
+    /// Write the results of each query to its own file `query_<n>_<keywords>.<ext>` in this directory instead of
+    /// stdout, numbering the queries from 1. Characters of the keywords other than letters, digits, '-' and '_'
+    /// become '-' in the file name.
+    #[arg(long, value_name = "DIR")]
+    output_dir: Option<PathBuf>,
+
+    /// Treat keywords farther than this many hops from a node as unreachable
+    #[arg(long, value_name = "HOPS")]
+    max_distance: Option<u32>,
+
+    /// Instead of the skyline, report the K nodes reaching every keyword with the best --rank-by score, best first
+    #[arg(long, value_name = "K")]
+    top_k: Option<usize>,
+
+    /// Score ranking the nodes for --top-k, smaller is better
+    #[arg(long, value_enum, default_value_t = RankBy::Sum)]
+    rank_by: RankBy,
+
+    /// Weights of the keywords of every query for --rank-by weighted-sum. Example: "1,0.5,2"
+    #[arg(
+        long,
+        value_name = "W1,W2,...",
+        value_delimiter = ',',
+        required_if_eq("rank_by", "weighted-sum")
+    )]
+    weights: Vec<f64>,
+
+    /// Compute the distances to every keyword of the input once and save them to this file
+    #[arg(long, value_name = "PATH", conflicts_with = "load_distances")]
+    save_distances: Option<PathBuf>,
+
+    /// Answer the queries from distances saved by --save-distances for the same input files
+    #[arg(long, value_name = "PATH")]
+    load_distances: Option<PathBuf>,
 
     /// List of keyword sets delimited by space. Example: "1,2,3 4,5,6"
     #[arg(value_parser = parse_keywords)]
-    queries: Vec<Vec<u32>>,
+    queries: Vec<Vec<String>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum EdgeFormat {
+    /// One `source: target1,target2,...` line per source node
+    Adjacency,
+    /// One whitespace-separated `source target [weight]` edge per line, lines starting with '#' are skipped
+    Pairs,
+    /// Like `pairs`, but comma-separated, with an optional header line
+    Csv,
+    /// Like `pairs`, but tab-separated, with an optional header line
+    Tsv,
+    /// GraphML file, with the node keywords in a `keywords` node attribute
+    Graphml,
+    /// Graphviz DOT file, with the node keywords in a `keywords` node attribute
+    Dot,
+}
+
+impl EdgeFormat {
+    /// Splits a line of an edge list format into its fields, or returns `None` for blank and comment lines.
+    fn edge_fields(self, line: &str) -> Option<Vec<&str>> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        Some(match self {
+            EdgeFormat::Csv => line.split(',').map(str::trim).collect(),
+            EdgeFormat::Tsv => line.split('\t').map(str::trim).collect(),
+            _ => line.split_whitespace().collect(),
+        })
+    }
+
+    /// Whether the file also holds the node keywords, so that no node keyword file is needed.
+    fn has_keywords(self) -> bool {
+        matches!(self, EdgeFormat::Graphml | EdgeFormat::Dot)
+    }
+
+    /// Whether the first line may be a header such as `source,target`.
+    fn has_header(self) -> bool {
+        matches!(self, EdgeFormat::Csv | EdgeFormat::Tsv)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum RankBy {
+    /// Sum of the distances
+    Sum,
+    /// Largest distance
+    Max,
+    /// Sum of the distances multiplied by --weights
+    WeightedSum,
+}
+
+impl RankBy {
+    fn aggregate(self, weights: &[f64]) -> skyline::Aggregate {
+        match self {
+            RankBy::Sum => skyline::Aggregate::Sum,
+            RankBy::Max => skyline::Aggregate::Max,
+            RankBy::WeightedSum => skyline::Aggregate::WeightedSum {
+                weights: weights.to_vec(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// One line per node and keyword
+    Text,
+    /// One JSON object per node and line, written as soon as it is produced
+    Ndjson,
+    /// Aligned table with one row per node and one column per keyword
+    Table,
+    /// Comma-separated `query,node,keyword,distance` rows below a single header
+    Csv,
+    /// One JSON array with an object per query holding its keywords, skyline and timings
+    Json,
+}
+
+impl Format {
+    fn is_machine_readable(self) -> bool {
+        matches!(self, Format::Ndjson | Format::Csv | Format::Json)
+    }
+
+    /// File extension used by `--output-dir`.
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Text | Format::Table => "txt",
+            Format::Ndjson => "ndjson",
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+/// Where and how much diagnostics are printed.
+#[derive(Clone, Copy, Debug)]
+struct Log {
+    verbosity: Verbosity,
+    /// Diagnostics go to stderr if stdout carries machine-readable results.
+    to_stderr: bool,
+}
+
+/// Prints a diagnostic if the verbosity of `$log` is at least `$level`.
+macro_rules! log {
+    ($log:expr, $level:expr, $($arg:tt)*) => {
+        if $log.verbosity >= $level {
+            if $log.to_stderr {
+                eprintln!($($arg)*);
+            } else {
+                println!($($arg)*);
+            }
+        }
+    };
+}
+
+/// Prints an explicitly requested report, even in quiet mode.
+macro_rules! report {
+    ($log:expr, $($arg:tt)*) => { log!($log, Verbosity::Quiet, $($arg)*) };
+}
+
+/// Prints a default diagnostic, hidden in quiet mode.
+macro_rules! info {
+    ($log:expr, $($arg:tt)*) => { log!($log, Verbosity::Normal, $($arg)*) };
 }
 
-fn parse_keywords(s: &str) -> Result<Vec<u32>> {
-    Ok(s.split(',').map(|k| k.parse()).try_collect()?)
+/// Prints an extra diagnostic, shown in verbose mode only.
+macro_rules! verbose {
+    ($log:expr, $($arg:tt)*) => { log!($log, Verbosity::Verbose, $($arg)*) };
+}
+
+fn parse_keywords(s: &str) -> Result<Vec<String>> {
+    Ok(s.split(',').map(str::to_owned).collect())
+}
+
+/// Resolves query keywords to ids: parsed as integers, or looked up in `interner` with `--named-keywords`. Unknown
+/// names get a fresh id, which occurs in no node.
+fn resolve_keywords(
+    queries: &[Vec<String>],
+    interner: Option<&mut KeywordInterner>,
+) -> Result<Vec<Vec<u32>>> {
+    match interner {
+        Some(interner) => Ok(queries
+            .iter()
+            .map(|keywords| keywords.iter().map(|name| interner.intern(name)).collect())
+            .collect()),
+        None => Ok(queries
+            .iter()
+            .map(|keywords| keywords.iter().map(|k| k.parse()).try_collect())
+            .try_collect()?),
+    }
+}
+
+fn parse_sink(s: &str) -> Result<(Format, PathBuf)> {
+    let (format, path) = s
+        .split_once(':')
+        .ok_or(anyhow!("expect FORMAT:PATH, got '{}'", s))?;
+    let format = Format::from_str(format, true).map_err(|e| anyhow!(e))?;
+    Ok((format, PathBuf::from(path)))
 }
 
 type BuildResult = Result<(DiGraphMap<u32, ()>, HashMap<u32, Vec<u32>>)>;
 
-fn build_graph(edge_file_path: &Path, node_keyword_file_path: &Path) -> BuildResult {
+/// Builds the graph and merges the node keywords of all files. With `two_pass`, every file is read twice: the first
+/// pass counts nodes, edges and keyword lines so that the tables are allocated at their final size, which avoids
+/// the peak of growing them while reading.
+fn build_graph(
+    edge_file_path: &Path,
+    edge_format: EdgeFormat,
+    node_keyword_file_paths: &[PathBuf],
+    two_pass: bool,
+    mut interner: Option<&mut KeywordInterner>,
+) -> BuildResult {
+    if node_keyword_file_paths.is_empty() && !edge_format.has_keywords() {
+        return Err(anyhow!(
+            "expect a node keyword file, the {} edge format has no keywords.",
+            edge_format.to_possible_value().unwrap().get_name()
+        ));
+    }
+    let (num_nodes, num_edges) = if two_pass {
+        count_nodes_and_edges(edge_file_path, edge_format)?
+    } else {
+        (0, 0)
+    };
+    let graph = DiGraphMap::with_capacity(num_nodes, num_edges);
+    let mut node_to_keyword: HashMap<u32, Vec<u32>> = HashMap::new();
+    let graph = match edge_format {
+        EdgeFormat::Adjacency | EdgeFormat::Pairs | EdgeFormat::Csv | EdgeFormat::Tsv => {
+            read_edges(edge_file_path, graph, edge_format)?
+        }
+        EdgeFormat::Graphml | EdgeFormat::Dot => {
+            let read = match edge_format {
+                EdgeFormat::Graphml => graph_files::read_graphml,
+                _ => graph_files::read_dot,
+            };
+            let (graph, file_keywords) = read(edge_file_path, interner.as_deref_mut())
+                .map_err(|e| anyhow!("{} in {}", e, edge_file_path.display()))?;
+            node_to_keyword = file_keywords;
+            graph
+        }
+    };
+    for node_keyword_file_path in node_keyword_file_paths {
+        let file_keywords =
+            read_node_keywords(node_keyword_file_path, two_pass, interner.as_deref_mut())?;
+        if node_to_keyword.is_empty() {
+            node_to_keyword = file_keywords;
+            continue;
+        }
+        // merge with the keywords of previous files
+        for (node, keywords) in file_keywords {
+            let node_keywords = node_to_keyword.entry(node).or_default();
+            node_keywords.extend(keywords);
+            node_keywords.sort_unstable();
+            node_keywords.dedup();
+        }
+    }
+    Ok((graph, node_to_keyword))
+}
+
+/// First pass of a two-pass build. Malformed lines are skipped here and reported by the second pass.
+fn count_nodes_and_edges(edge_file_path: &Path, edge_format: EdgeFormat) -> Result<(usize, usize)> {
+    // GraphML and DOT files are parsed as a whole, so there is no growing table to avoid
+    if edge_format.has_keywords() {
+        return Ok((0, 0));
+    }
+    let reader = BufReader::new(File::open(edge_file_path)?);
+    let mut nodes = HashSet::new();
+    let mut num_edges = 0;
+    let mut first_edge = true;
+    for line in reader.lines() {
+        let Ok(Some(edges)) = parse_edge_line(edge_format, &line?, &mut first_edge) else {
+            continue;
+        };
+        for (source, target) in edges {
+            nodes.insert(source);
+            nodes.insert(target);
+            num_edges += 1;
+        }
+    }
+    Ok((nodes.len(), num_edges))
+}
+
+/// Parses one line of an edge file in `edge_format`, which should not be GraphML or DOT, into its edges. Returns
+/// `None` for a line holding no edge: a blank or comment line, or a header, which `first_edge` only allows before
+/// the first edge. Weights are checked to be numbers but not used, as the graph is unweighted. Errors leave out the
+/// location of the line.
+fn parse_edge_line(
+    edge_format: EdgeFormat,
+    line: &str,
+    first_edge: &mut bool,
+) -> Result<Option<Vec<(u32, u32)>>, String> {
+    let parse_node = |node: &str| {
+        node.parse::<u32>()
+            .map_err(|_| format!("invalid node '{}'", node))
+    };
+    if edge_format == EdgeFormat::Adjacency {
+        let (source, targets) = line.split_once(':').ok_or("expect ':'")?;
+        let source = parse_node(source)?;
+        return targets
+            .trim_matches(|x: char| x.is_whitespace() || x == ',')
+            .split(',')
+            .map(|target| Ok((source, parse_node(target)?)))
+            .try_collect()
+            .map(Some);
+    }
+    // skip blank and comment lines
+    let Some(fields) = edge_format.edge_fields(line) else {
+        return Ok(None);
+    };
+    // a header names the columns instead of holding node ids
+    let is_header = *first_edge && edge_format.has_header() && fields[0].parse::<u32>().is_err();
+    *first_edge = false;
+    if is_header {
+        return Ok(None);
+    }
+    match fields.as_slice() {
+        [source, target] => Ok(Some(vec![(parse_node(source)?, parse_node(target)?)])),
+        [source, target, weight] if weight.parse::<f64>().is_ok() => {
+            Ok(Some(vec![(parse_node(source)?, parse_node(target)?)]))
+        }
+        _ => Err("expect 'source target [weight]'".to_owned()),
+    }
+}
+
+/// Reads an edge file in `edge_format`, which should not be GraphML or DOT.
+fn read_edges(
+    edge_file_path: &Path,
+    mut graph: DiGraphMap<u32, ()>,
+    edge_format: EdgeFormat,
+) -> Result<DiGraphMap<u32, ()>> {
     let edge_file = File::open(edge_file_path)?;
-    let node_keyword_file = File::open(node_keyword_file_path)?;
-    let mut graph = DiGraphMap::new();
     let reader = BufReader::new(edge_file);
+    let mut first_edge = true;
     for (line_number, line) in reader.lines().enumerate() {
-        let line = line?;
-        let (source, targets) = line.split_once(':').ok_or(anyhow!(
-            "expect ':' at line {} in {}",
-            line_number + 1,
-            edge_file_path.display()
-        ))?;
-        let source = source.parse()?;
-        for target in targets
-            .trim_matches(|x: char| x.is_whitespace() || x == ',')
-            .split(',')
-            .map(|t| t.parse::<u32>())
-        {
-            let target = target?;
-            if graph.add_edge(source, target, ()).is_some() {
-                return Err(anyhow!(
-                    "duplicate edge found: source: {}, target: {}.",
-                    source,
-                    target
-                ));
-            }
+        let edges = parse_edge_line(edge_format, &line?, &mut first_edge).map_err(|e| {
+            anyhow!(
+                "{} at line {} in {}",
+                e,
+                line_number + 1,
+                edge_file_path.display()
+            )
+        })?;
+        for (source, target) in edges.into_iter().flatten() {
+            add_edge(&mut graph, source, target)?;
         }
     }
-    let mut node_to_keyword = HashMap::new();
+    Ok(graph)
+}
+
+fn add_edge(graph: &mut DiGraphMap<u32, ()>, source: u32, target: u32) -> Result<()> {
+    if graph.add_edge(source, target, ()).is_some() {
+        return Err(anyhow!(
+            "duplicate edge found: source: {}, target: {}.",
+            source,
+            target
+        ));
+    }
+    Ok(())
+}
+
+/// Reads one node keyword file. With `compact`, the file is read twice to allocate the map at its final size, and
+/// the keyword vectors are shrunk to fit. They stay one vector per node, as the queries take them in that shape.
+fn read_node_keywords(
+    node_keyword_file_path: &Path,
+    compact: bool,
+    mut interner: Option<&mut KeywordInterner>,
+) -> Result<HashMap<u32, Vec<u32>>> {
+    let num_lines = if compact {
+        BufReader::new(File::open(node_keyword_file_path)?)
+            .lines()
+            .count()
+    } else {
+        0
+    };
+    let node_keyword_file = File::open(node_keyword_file_path)?;
+    let mut node_to_keyword = HashMap::with_capacity(num_lines);
     let reader = BufReader::new(node_keyword_file);
     for (line_number, line) in reader.lines().enumerate() {
-        let line = line?;
-        let (node, keywords) = line.split_once(':').ok_or(anyhow!(
-            "expect ':' at line {} in {}",
-            line_number + 1,
-            node_keyword_file_path.display()
-        ))?;
-        let node = node.parse()?;
-        let mut keywords: Vec<_> = keywords
-            .trim_matches(|x: char| x.is_whitespace() || x == ',')
-            .split(',')
-            .map(|t| t.parse::<u32>())
-            .try_collect()?;
-        keywords.sort_unstable();
+        let (node, mut keywords) = parse_node_keyword_line(&line?, interner.as_deref_mut())
+            .and_then(|(node, keywords)| {
+                if keywords.is_empty() {
+                    Err("empty keyword list".to_owned())
+                } else {
+                    Ok((node, keywords))
+                }
+            })
+            .map_err(|e| {
+                anyhow!(
+                    "{} at line {} in {}",
+                    e,
+                    line_number + 1,
+                    node_keyword_file_path.display()
+                )
+            })?;
+        if compact {
+            keywords.shrink_to_fit();
+        }
         if node_to_keyword.insert(node, keywords).is_some() {
-            return Err(anyhow!("duplicate node found: {}.", node));
+            return Err(anyhow!(
+                "duplicate node found: {} in {}.",
+                node,
+                node_keyword_file_path.display()
+            ));
         }
     }
-    Ok((graph, node_to_keyword))
+    Ok(node_to_keyword)
+}
+
+/// Parses one `node: keywords` line of a node keyword file, interning names with `interner`. A blank list gives no
+/// keywords. Errors leave out the location of the line.
+fn parse_node_keyword_line(
+    line: &str,
+    interner: Option<&mut KeywordInterner>,
+) -> Result<(u32, Vec<u32>), String> {
+    let (node, keywords) = line.split_once(':').ok_or("expect ':'")?;
+    let node = node
+        .parse()
+        .map_err(|_| format!("invalid node '{}'", node))?;
+    if keywords
+        .trim_matches(|x: char| x.is_whitespace() || x == ',')
+        .is_empty()
+    {
+        return Ok((node, Vec::new()));
+    }
+    let keywords = parse_keyword_list(keywords, interner)
+        .map_err(|e| format!("invalid keyword list of node {}: {}", node, e))?;
+    Ok((node, keywords))
+}
+
+/// Parses a comma-separated keyword list into sorted keyword ids, interning names with `interner`.
+fn parse_keyword_list(list: &str, interner: Option<&mut KeywordInterner>) -> Result<Vec<u32>> {
+    let keywords = list
+        .trim_matches(|x: char| x.is_whitespace() || x == ',')
+        .split(',');
+    let mut keywords: Vec<_> = match interner {
+        Some(interner) => keywords.map(|t| interner.intern(t)).collect(),
+        None => keywords.map(|t| t.parse::<u32>()).try_collect()?,
+    };
+    // files are usually pre-sorted, so only sort when needed
+    if keywords.windows(2).any(|w| w[0] > w[1]) {
+        keywords.sort_unstable();
+    }
+    Ok(keywords)
+}
+
+/// Resident set size of this process, or its peak so far with `peak`, where the platform reports it (Linux).
+fn memory_kib(peak: bool) -> Option<u64> {
+    let field = if peak { "VmHWM:" } else { "VmRSS:" };
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(field))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+/// Prints keyword ids as given, or as their names with `--named-keywords`.
+#[derive(Clone, Copy)]
+struct Labels<'a>(Option<&'a KeywordInterner>);
+
+impl Labels<'_> {
+    fn text(self, keyword: u32) -> String {
+        match self.0 {
+            Some(interner) => interner.name(keyword).unwrap().to_owned(),
+            None => keyword.to_string(),
+        }
+    }
+
+    /// Keyword as a JSON value: a number, or a string for names.
+    fn json(self, keyword: u32) -> String {
+        match self.0 {
+            Some(_) => json_string(&self.text(keyword)),
+            None => keyword.to_string(),
+        }
+    }
+
+    /// Keywords as a `[k1, k2, ...]` list.
+    fn list(self, keywords: &[u32]) -> String {
+        let keywords: Vec<_> = keywords.iter().map(|&k| self.text(k)).collect();
+        format!("[{}]", keywords.join(", "))
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Time spent on each phase of a query.
+struct QueryTimes {
+    bfs: Duration,
+    filter: Duration,
+}
+
+/// Destination of query results in some format.
+trait ResultSink {
+    /// Writes the skyline `result` of the query for `keywords`, computed in `times`.
+    fn write(
+        &mut self,
+        keywords: &[u32],
+        result: &[(u32, Vec<u32>)],
+        times: &QueryTimes,
+    ) -> io::Result<()>;
+
+    /// Completes the output after the last query.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Creates a sink writing `format` to `out`, with keywords printed by `labels`.
+fn new_sink<'a>(
+    format: Format,
+    out: impl Write + 'a,
+    labels: Labels<'a>,
+) -> Box<dyn ResultSink + 'a> {
+    match format {
+        Format::Text => Box::new(TextSink { out, labels }),
+        Format::Ndjson => Box::new(NdjsonSink { out, labels }),
+        Format::Table => Box::new(TableSink { out, labels }),
+        Format::Csv => Box::new(CsvSink {
+            out,
+            labels,
+            header_written: false,
+        }),
+        Format::Json => Box::new(JsonSink {
+            out,
+            labels,
+            num_queries: 0,
+        }),
+    }
+}
+
+struct TextSink<'a, W> {
+    out: W,
+    labels: Labels<'a>,
+}
+
+impl<W: Write> ResultSink for TextSink<'_, W> {
+    fn write(
+        &mut self,
+        keywords: &[u32],
+        result: &[(u32, Vec<u32>)],
+        _times: &QueryTimes,
+    ) -> io::Result<()> {
+        for (root, dist) in result {
+            for (&k, d) in keywords.iter().zip(dist) {
+                writeln!(self.out, "{}: {} distance {}", root, self.labels.text(k), d)?;
+            }
+            writeln!(self.out)?;
+        }
+        self.out.flush()
+    }
+}
+
+struct NdjsonSink<'a, W> {
+    out: W,
+    labels: Labels<'a>,
+}
+
+impl<W: Write> ResultSink for NdjsonSink<'_, W> {
+    fn write(
+        &mut self,
+        keywords: &[u32],
+        result: &[(u32, Vec<u32>)],
+        _times: &QueryTimes,
+    ) -> io::Result<()> {
+        let keywords: Vec<_> = keywords.iter().map(|&k| self.labels.json(k)).collect();
+        let keywords = keywords.join(", ");
+        for (root, dist) in result {
+            writeln!(
+                self.out,
+                "{{\"keywords\":[{}],\"node\":{},\"distances\":{:?}}}",
+                keywords, root, dist
+            )?;
+            // consumers can process each line as soon as it is written
+            self.out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+struct TableSink<'a, W> {
+    out: W,
+    labels: Labels<'a>,
+}
+
+impl<W: Write> ResultSink for TableSink<'_, W> {
+    fn write(
+        &mut self,
+        keywords: &[u32],
+        result: &[(u32, Vec<u32>)],
+        _times: &QueryTimes,
+    ) -> io::Result<()> {
+        let header: Vec<_> = std::iter::once("node".to_owned())
+            .chain(keywords.iter().map(|&k| self.labels.text(k)))
+            .collect();
+        let rows: Vec<Vec<_>> = result
+            .iter()
+            .map(|(root, dist)| {
+                std::iter::once(root.to_string())
+                    .chain(dist.iter().map(u32::to_string))
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<_> = (0..header.len())
+            .map(|col| {
+                std::iter::once(&header)
+                    .chain(&rows)
+                    .map(|row| row[col].len())
+                    .max()
+                    .unwrap()
+            })
+            .collect();
+        for row in std::iter::once(&header).chain(&rows) {
+            let cells: Vec<_> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{:>width$}", cell))
+                .collect();
+            writeln!(self.out, "{}", cells.join("  "))?;
+        }
+        self.out.flush()
+    }
+}
+
+struct CsvSink<'a, W> {
+    out: W,
+    labels: Labels<'a>,
+    header_written: bool,
+}
+
+impl<W: Write> ResultSink for CsvSink<'_, W> {
+    fn write(
+        &mut self,
+        keywords: &[u32],
+        result: &[(u32, Vec<u32>)],
+        _times: &QueryTimes,
+    ) -> io::Result<()> {
+        if !self.header_written {
+            writeln!(self.out, "query,node,keyword,distance")?;
+            self.header_written = true;
+        }
+        let query: Vec<_> = keywords.iter().map(|&k| self.labels.text(k)).collect();
+        for (root, dist) in result {
+            for (k, d) in query.iter().zip(dist) {
+                writeln!(self.out, "{},{},{},{}", query.join(" "), root, k, d)?;
+            }
+        }
+        self.out.flush()
+    }
+}
+
+struct JsonSink<'a, W> {
+    out: W,
+    labels: Labels<'a>,
+    num_queries: usize,
+}
+
+impl<W: Write> ResultSink for JsonSink<'_, W> {
+    fn write(
+        &mut self,
+        keywords: &[u32],
+        result: &[(u32, Vec<u32>)],
+        times: &QueryTimes,
+    ) -> io::Result<()> {
+        // objects are written as queries complete, the array is closed by `finish`
+        write!(
+            self.out,
+            "{}",
+            if self.num_queries == 0 { "[\n" } else { ",\n" }
+        )?;
+        self.num_queries += 1;
+        let keywords: Vec<_> = keywords.iter().map(|&k| self.labels.json(k)).collect();
+        let skyline: Vec<_> = result
+            .iter()
+            .map(|(root, dist)| format!("{{\"node\":{},\"distances\":{:?}}}", root, dist))
+            .collect();
+        write!(
+            self.out,
+            "{{\"keywords\":[{}],\"skyline\":[{}],\"timings\":{{\"bfs\":{},\"filter\":{},\"total\":{}}}}}",
+            keywords.join(","),
+            skyline.join(","),
+            times.bfs.as_secs_f64(),
+            times.filter.as_secs_f64(),
+            (times.bfs + times.filter).as_secs_f64()
+        )?;
+        self.out.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.num_queries == 0 {
+            write!(self.out, "[")?;
+        }
+        writeln!(self.out, "\n]")?;
+        self.out.flush()
+    }
+}
+
+/// Distances from each node to every keyword of the input, saved by `--save-distances`.
+struct DistanceCache {
+    /// Hash of the input the distances were computed for, see `cache_key`.
+    key: u64,
+    index: skyline::KeywordDistanceIndex<u32, u32, u32>,
+}
+
+impl DistanceCache {
+    fn compute(graph: &DiGraphMap<u32, ()>, node_to_keyword: &HashMap<u32, Vec<u32>>) -> Self {
+        DistanceCache {
+            key: cache_key(graph, node_to_keyword),
+            index: skyline::KeywordDistanceIndex::new(graph, node_to_keyword),
+        }
+    }
+
+    /// Distances for the columns of `keywords`. Keywords outside the index are unreachable from every node.
+    fn select(&self, keywords: &[u32]) -> skyline::DistanceMap<u32, u32> {
+        let universe = self.index.keywords();
+        let columns: Vec<_> = keywords
+            .iter()
+            .map(|keyword| universe.binary_search(keyword).ok())
+            .collect();
+        self.index
+            .distances()
+            .iter()
+            .map(|(&node, dist)| {
+                let dist = columns
+                    .iter()
+                    .map(|column| column.map_or(u32::MAX, |column| dist[column]))
+                    .collect();
+                (node, dist)
+            })
+            .collect()
+    }
+}
+
+/// Hashes the graph and the node keywords, so a distance cache can be matched with its input. Every count and id is
+/// fed to the crate's own Fx hash as a whole integer, bypassing the standard `Hash` impls, so saved caches stay
+/// valid across builds, platforms and releases of the standard library.
+fn cache_key(graph: &DiGraphMap<u32, ()>, node_to_keyword: &HashMap<u32, Vec<u32>>) -> u64 {
+    let mut nodes: Vec<_> = graph.nodes().collect();
+    nodes.sort_unstable();
+    let mut edges: Vec<_> = graph.all_edges().map(|(u, v, _)| (u, v)).collect();
+    edges.sort_unstable();
+    let mut node_keywords: Vec<_> = node_to_keyword.iter().collect();
+    node_keywords.sort_unstable();
+    let mut hasher = FxHasher::default();
+    hasher.write_u64(nodes.len() as u64);
+    for node in nodes {
+        hasher.write_u32(node);
+    }
+    hasher.write_u64(edges.len() as u64);
+    for (source, target) in edges {
+        hasher.write_u32(source);
+        hasher.write_u32(target);
+    }
+    hasher.write_u64(node_keywords.len() as u64);
+    for (&node, keywords) in node_keywords {
+        hasher.write_u32(node);
+        hasher.write_u64(keywords.len() as u64);
+        for &keyword in keywords {
+            hasher.write_u32(keyword);
+        }
+    }
+    hasher.finish()
+}
+
+fn save_distances(path: &Path, cache: &DistanceCache) -> Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "key: {:016x}", cache.key)?;
+    skyline::save_index(&cache.index, out)?;
+    Ok(())
+}
+
+/// Loads distances saved by `save_distances`, rejecting them unless they were computed for the input hashed as
+/// `key`.
+fn load_distances(path: &Path, key: u64) -> Result<DistanceCache> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut header = String::new();
+    reader.read_line(&mut header)?;
+    let saved_key = header
+        .trim_end()
+        .strip_prefix("key: ")
+        .ok_or(anyhow!("expect 'key:' header in {}", path.display()))?;
+    if u64::from_str_radix(saved_key, 16)? != key {
+        return Err(anyhow!(
+            "{} was saved for different input files.",
+            path.display()
+        ));
+    }
+    // line numbers of the index start after the key
+    let index = skyline::load_index(reader)
+        .map_err(|err| anyhow!("{} below the key in {}", err, path.display()))?;
+    Ok(DistanceCache { key, index })
+}
+
+/// Problems found by `validate`. Errors make queries fail or silently misbehave, warnings are likely data mistakes.
+#[derive(Debug, Default)]
+struct ValidationReport {
+    nodes: usize,
+    edges: usize,
+    malformed_lines: usize,
+    duplicate_edges: usize,
+    duplicate_nodes: usize,
+    empty_keyword_lists: usize,
+    self_loops: usize,
+    /// Edges whose target is not listed in the node keywords, e.g. a typo in the edge file.
+    dangling_targets: usize,
+    keyword_nodes_missing_from_graph: usize,
+    /// One message per error, with the file and line it was found at.
+    diagnostics: Vec<String>,
+}
+
+impl ValidationReport {
+    fn errors(&self) -> usize {
+        self.malformed_lines
+            + self.duplicate_edges
+            + self.duplicate_nodes
+            + self.empty_keyword_lists
+    }
+}
+
+fn validate(args: &ValidateArgs) -> Result<ValidationReport> {
+    let mut report = ValidationReport::default();
+    if args.node_keyword_file.is_empty() && !args.edge_format.has_keywords() {
+        return Err(anyhow!(
+            "expect a node keyword file, the {} edge format has no keywords.",
+            args.edge_format.to_possible_value().unwrap().get_name()
+        ));
+    }
+    let mut graph = DiGraphMap::<u32, ()>::new();
+    // nodes with an entry in the node keywords, in the edge file or the node keyword files
+    let mut listed = HashSet::new();
+    let mut interner = KeywordInterner::new();
+    if args.edge_format.has_keywords() {
+        // these files are parsed as a whole, so the first problem fails the validation
+        let interner = args.named_keywords.then_some(&mut interner);
+        let read = match args.edge_format {
+            EdgeFormat::Graphml => graph_files::read_graphml,
+            _ => graph_files::read_dot,
+        };
+        let (edge_graph, node_to_keyword) = read(&args.edge_file, interner)
+            .map_err(|e| anyhow!("{} in {}", e, args.edge_file.display()))?;
+        graph = edge_graph;
+        listed.extend(node_to_keyword.into_keys());
+    } else {
+        let reader = BufReader::new(File::open(&args.edge_file)?);
+        let mut first_edge = true;
+        for (line_number, line) in reader.lines().enumerate() {
+            let at = || format!("line {} in {}", line_number + 1, args.edge_file.display());
+            let edges = match parse_edge_line(args.edge_format, &line?, &mut first_edge) {
+                Ok(edges) => edges.unwrap_or_default(),
+                Err(e) => {
+                    report.malformed_lines += 1;
+                    report.diagnostics.push(format!("{} at {}", e, at()));
+                    continue;
+                }
+            };
+            for (source, target) in edges {
+                if graph.add_edge(source, target, ()).is_some() {
+                    report.duplicate_edges += 1;
+                    report.diagnostics.push(format!(
+                        "duplicate edge from {} to {} at {}",
+                        source,
+                        target,
+                        at()
+                    ));
+                } else if source == target {
+                    report.self_loops += 1;
+                }
+            }
+        }
+    }
+    report.nodes = graph.node_count();
+    report.edges = graph.edge_count();
+    for node_keyword_file_path in &args.node_keyword_file {
+        let mut seen = HashSet::new();
+        let reader = BufReader::new(File::open(node_keyword_file_path)?);
+        for (line_number, line) in reader.lines().enumerate() {
+            let at = || {
+                format!(
+                    "line {} in {}",
+                    line_number + 1,
+                    node_keyword_file_path.display()
+                )
+            };
+            let interner = args.named_keywords.then_some(&mut interner);
+            let (node, keywords) = match parse_node_keyword_line(&line?, interner) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    report.malformed_lines += 1;
+                    report.diagnostics.push(format!("{} at {}", e, at()));
+                    continue;
+                }
+            };
+            if !seen.insert(node) {
+                report.duplicate_nodes += 1;
+                report
+                    .diagnostics
+                    .push(format!("duplicate node {} at {}", node, at()));
+            }
+            listed.insert(node);
+            if !graph.contains_node(node) {
+                report.keyword_nodes_missing_from_graph += 1;
+            }
+            if keywords.is_empty() {
+                report.empty_keyword_lists += 1;
+                report
+                    .diagnostics
+                    .push(format!("empty keyword list at {}", at()));
+            }
+        }
+    }
+    report.dangling_targets = graph
+        .all_edges()
+        .filter(|(_, target, _)| !listed.contains(target))
+        .count();
+    Ok(report)
+}
+
+fn run_validate(args: &ValidateArgs) -> Result<()> {
+    let report = validate(args)?;
+    println!("Nodes: {}", report.nodes);
+    println!("Edges: {}", report.edges);
+    println!("Errors:");
+    println!("  malformed lines: {}", report.malformed_lines);
+    println!("  duplicate edges: {}", report.duplicate_edges);
+    println!("  duplicate nodes: {}", report.duplicate_nodes);
+    println!("  empty keyword lists: {}", report.empty_keyword_lists);
+    println!("Warnings:");
+    println!("  self-loops: {}", report.self_loops);
+    println!("  dangling targets: {}", report.dangling_targets);
+    println!(
+        "  keyword nodes missing from the graph: {}",
+        report.keyword_nodes_missing_from_graph
+    );
+    if report.errors() > 0 {
+        for diagnostic in &report.diagnostics {
+            eprintln!("{}", diagnostic);
+        }
+        return Err(anyhow!("validation found {} errors.", report.errors()));
+    }
+    Ok(())
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    run(Cli::parse())
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let args = match (cli.command, cli.query) {
+        (Some(Command::Validate(args)), _) => return run_validate(&args),
+        (None, Some(args)) => args,
+        (None, None) => unreachable!("clap requires the query arguments without a subcommand"),
+    };
+    let log = Log {
+        verbosity: if args.quiet {
+            Verbosity::Quiet
+        } else if args.verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        },
+        to_stderr: args.format.is_machine_readable(),
+    };
+    if args.rank_by == RankBy::WeightedSum {
+        if let Some(keywords) = args
+            .queries
+            .iter()
+            .find(|keywords| keywords.len() != args.weights.len())
+        {
+            return Err(anyhow!(
+                "expect one weight per keyword, found {} weights for query {}.",
+                args.weights.len(),
+                keywords.join(",")
+            ));
+        }
+    }
+    let rank_by = args.rank_by.aggregate(&args.weights);
+    let mut interner = args.named_keywords.then(KeywordInterner::new);
+    // integer queries are checked before the build, names can only be resolved after it
+    let mut queries = match interner {
+        Some(_) => Vec::new(),
+        None => resolve_keywords(&args.queries, None)?,
+    };
+    let memory_before = memory_kib(false);
     let start = Instant::now();
-    let (graph, node_to_keyword) = build_graph(&args.edge_file, &args.node_keyword_file)?;
+    let (graph, node_to_keyword) = build_graph(
+        &args.edge_file,
+        args.edge_format,
+        &args.node_keyword_file,
+        args.two_pass_build,
+        interner.as_mut(),
+    )?;
+    if interner.is_some() {
+        queries = resolve_keywords(&args.queries, interner.as_mut())?;
+    }
+    let labels = Labels(interner.as_ref());
     let building_time = start.elapsed();
-    println!("Building graph: {}", building_time.as_secs_f64());
+    info!(log, "Building graph: {}", building_time.as_secs_f64());
+    if args.mem {
+        let kib = |memory: Option<u64>| {
+            memory.map_or("unavailable".to_owned(), |kib| format!("{} KiB", kib))
+        };
+        report!(
+            log,
+            "Memory before building: {}, after building: {}, peak: {}",
+            kib(memory_before),
+            kib(memory_kib(false)),
+            kib(memory_kib(true))
+        );
+    }
+    verbose!(
+        log,
+        "Nodes: {}, edges: {}, nodes with keywords: {}",
+        graph.node_count(),
+        graph.edge_count(),
+        node_to_keyword.len()
+    );
+    verbose!(
+        log,
+        "Keyword search hasher: {}",
+        if cfg!(feature = "fast-hash") {
+            "Fx (fast-hash)"
+        } else {
+            "SipHash"
+        }
+    );
+    if graph.node_count() == 0 {
+        report!(log, "graph is empty.");
+        return Ok(());
+    }
+    if args.check_dag {
+        report!(log, "Graph is a DAG: {}", !is_cyclic_directed(&graph));
+    }
+
+    let keyword_universe: HashSet<_> = node_to_keyword.values().flatten().collect();
+    for &keyword in queries.iter().flatten() {
+        if !keyword_universe.contains(&keyword) {
+            let keyword = labels.text(keyword);
+            if args.strict {
+                return Err(anyhow!("keyword {} does not occur in any node.", keyword));
+            }
+            eprintln!("warning: keyword {} does not occur in any node.", keyword);
+        }
+    }
 
-    for keywords in args.queries {
+    if args.dry_run {
+        report!(log, "Dry run: {} queries", queries.len());
+        for keywords in &queries {
+            report!(
+                log,
+                "Keywords: {}, estimated work: {}, distance matrix: {} bytes",
+                labels.list(keywords),
+                skyline::estimated_work(graph.node_count(), keywords.len()),
+                skyline::estimated_distance_map_bytes::<u32, u32>(
+                    graph.node_count(),
+                    keywords.len()
+                )
+            );
+        }
+        return Ok(());
+    }
+
+    let cache = if let Some(path) = &args.load_distances {
         let start = Instant::now();
-        let result =
-            skyline::semantic_place_skyline::<_, _, u32>(&graph, &node_to_keyword, &keywords);
-        let exec_time = start.elapsed();
-        println!("Keywords: {:?}", keywords);
-        println!("Execution time: {}", exec_time.as_secs_f64());
-        for (root, dist) in result {
-            for (k, d) in keywords.iter().zip(dist) {
-                println!("{}: {} distance {}", root, k, d);
+        let cache = load_distances(path, cache_key(&graph, &node_to_keyword))?;
+        info!(log, "Loading distances: {}", start.elapsed().as_secs_f64());
+        Some(cache)
+    } else if let Some(path) = &args.save_distances {
+        let start = Instant::now();
+        let cache = DistanceCache::compute(&graph, &node_to_keyword);
+        save_distances(path, &cache)?;
+        info!(log, "Saving distances: {}", start.elapsed().as_secs_f64());
+        Some(cache)
+    } else {
+        None
+    };
+
+    if let Some(output_dir) = &args.output_dir {
+        fs::create_dir_all(output_dir)?;
+    }
+    let mut sinks: Vec<_> = args
+        .sink
+        .iter()
+        .map(|(format, path)| {
+            Ok(new_sink(
+                *format,
+                BufWriter::new(File::create(path)?),
+                labels,
+            ))
+        })
+        .collect::<Result<_>>()?;
+    let mut stdout = new_sink(args.format, io::stdout().lock(), labels);
+    for (query_idx, keywords) in queries.into_iter().enumerate() {
+        let start = Instant::now();
+        // a cache hit skips the search
+        let dists = match (&cache, args.max_distance) {
+            (Some(cache), max_distance) => {
+                let mut dists = cache.select(&keywords);
+                if let Some(max_distance) = max_distance {
+                    dists.values_mut().flatten().for_each(|dist| {
+                        if *dist > max_distance {
+                            *dist = u32::MAX;
+                        }
+                    });
+                }
+                dists
+            }
+            (None, Some(max_distance)) => {
+                skyline::keyword_distances_within(&graph, &node_to_keyword, &keywords, max_distance)
+            }
+            (None, None) => {
+                skyline::keyword_distances::<_, _, u32>(&graph, &node_to_keyword, &keywords)
             }
-            println!();
+        };
+        let bfs_time = start.elapsed();
+        let start = Instant::now();
+        let result = match args.top_k {
+            Some(k) => skyline::top_k_by_score(&dists, k, &rank_by)
+                .into_iter()
+                .map(|(node, dist, _)| (node, dist))
+                .collect(),
+            None => skyline::minimal_elements(&dists),
+        };
+        let filter_time = start.elapsed();
+        let times = QueryTimes {
+            bfs: bfs_time,
+            filter: filter_time,
+        };
+        info!(log, "Keywords: {}", labels.list(&keywords));
+        info!(
+            log,
+            "Execution time: {}",
+            (bfs_time + filter_time).as_secs_f64()
+        );
+        info!(log, "Keyword BFS: {}", bfs_time.as_secs_f64());
+        info!(log, "Skyline filter: {}", filter_time.as_secs_f64());
+        for keyword in &keywords {
+            let sources = node_to_keyword
+                .values()
+                .filter(|node_keywords| node_keywords.binary_search(keyword).is_ok())
+                .count();
+            verbose!(
+                log,
+                "Keyword {}: {} source nodes",
+                labels.text(*keyword),
+                sources
+            );
+        }
+        verbose!(log, "Skyline size: {}", result.len());
+        if args.mem {
+            let bytes = skyline::estimated_distance_map_bytes::<u32, u32>(
+                graph.node_count(),
+                keywords.len(),
+            );
+            report!(log, "Distance matrix: {} bytes", bytes);
+        }
+        match &args.output_dir {
+            Some(output_dir) => {
+                let name: Vec<_> = keywords
+                    .iter()
+                    .map(|&k| file_name_part(&labels.text(k)))
+                    .collect();
+                // the query number keeps repeated queries apart
+                let path = output_dir.join(format!(
+                    "query_{}_{}.{}",
+                    query_idx + 1,
+                    name.join("_"),
+                    args.format.extension()
+                ));
+                info!(log, "Output: {}", path.display());
+                let mut sink = new_sink(args.format, BufWriter::new(File::create(path)?), labels);
+                sink.write(&keywords, &result, &times)?;
+                sink.finish()?;
+            }
+            None => stdout.write(&keywords, &result, &times)?,
+        }
+        for sink in &mut sinks {
+            sink.write(&keywords, &result, &times)?;
         }
     }
+    if args.output_dir.is_none() {
+        stdout.finish()?;
+    }
+    for sink in &mut sinks {
+        sink.finish()?;
+    }
     Ok(())
 }
+
+/// Replaces the characters of `text` that could leave the output directory or are not portable in file names, such
+/// as '/' and '.', by '-'.
+fn file_name_part(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh directory for the files of one test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("skyline-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn output_dir_writes_one_file_per_query() {
+        let dir = test_dir("output-dir");
+        let edge_file = dir.join("edges.txt");
+        let node_keyword_file = dir.join("nodes.txt");
+        fs::write(&edge_file, "1:2\n2:3\n").unwrap();
+        fs::write(&node_keyword_file, "1:coffee,../up\n3:park\n").unwrap();
+        let output_dir = dir.join("out");
+        let cli = Cli::try_parse_from([
+            "skyline".as_ref(),
+            "-e".as_ref(),
+            edge_file.as_os_str(),
+            "-n".as_ref(),
+            node_keyword_file.as_os_str(),
+            "--named-keywords".as_ref(),
+            "--output-dir".as_ref(),
+            output_dir.as_os_str(),
+            "-q".as_ref(),
+            "coffee,park".as_ref(),
+            "../up".as_ref(),
+            "coffee,park".as_ref(),
+        ])
+        .unwrap();
+        run(cli).unwrap();
+        let read_lines = |name: &str| {
+            let mut lines: Vec<_> = fs::read_to_string(output_dir.join(name))
+                .unwrap()
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(str::to_owned)
+                .collect();
+            lines.sort_unstable();
+            lines
+        };
+        let coffee_park = [
+            "1: coffee distance 0",
+            "1: park distance 2",
+            "3: coffee distance 4294967295",
+            "3: park distance 0",
+        ];
+        assert_eq!(read_lines("query_1_coffee_park.txt"), coffee_park);
+        assert_eq!(read_lines("query_2_---up.txt"), ["1: ../up distance 0"]);
+        assert_eq!(read_lines("query_3_coffee_park.txt"), coffee_park);
+        // the '/' of "../up" does not lead out of the directory
+        assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 3);
+        assert!(!dir.join("up.txt").exists());
+    }
+
+    #[test]
+    fn empty_graph_stops_before_the_queries() {
+        let dir = test_dir("empty-graph");
+        let edge_file = dir.join("edges.txt");
+        let node_keyword_file = dir.join("nodes.txt");
+        fs::write(&edge_file, "").unwrap();
+        fs::write(&node_keyword_file, "").unwrap();
+        let output_dir = dir.join("out");
+        let cli = Cli::try_parse_from([
+            "skyline".as_ref(),
+            "-e".as_ref(),
+            edge_file.as_os_str(),
+            "-n".as_ref(),
+            node_keyword_file.as_os_str(),
+            "--output-dir".as_ref(),
+            output_dir.as_os_str(),
+            "1,2".as_ref(),
+        ])
+        .unwrap();
+        run(cli).unwrap();
+        // "graph is empty." is reported before any query output is set up
+        assert!(!output_dir.exists());
+    }
+
+    #[test]
+    fn validate_counts_edges_to_unlisted_nodes() {
+        let dir = test_dir("validate");
+        let edge_file = dir.join("edges.txt");
+        let node_keyword_file = dir.join("nodes.txt");
+        // node 3 is a sink, node 4 is not in the node keywords
+        fs::write(&edge_file, "1:2,4\n2:3\n").unwrap();
+        fs::write(&node_keyword_file, "1:5\n2:6\n3:7\n").unwrap();
+        let args = ValidateArgs {
+            edge_file,
+            edge_format: EdgeFormat::Adjacency,
+            node_keyword_file: vec![node_keyword_file],
+            named_keywords: false,
+        };
+        let report = validate(&args).unwrap();
+        assert_eq!(report.edges, 3);
+        assert_eq!(report.dangling_targets, 1);
+        assert_eq!(report.errors(), 0);
+    }
+
+    #[test]
+    fn validate_reports_every_line_the_build_rejects() {
+        let dir = test_dir("validate-all");
+        let edge_file = dir.join("edges.csv");
+        let node_keyword_file = dir.join("nodes.txt");
+        fs::write(&edge_file, "source,target\n1,2\n1,x\n1,2\n2,3\n").unwrap();
+        fs::write(&node_keyword_file, "1:5\n2\n3:\n1:6\n").unwrap();
+        let args = ValidateArgs {
+            edge_file: edge_file.clone(),
+            edge_format: EdgeFormat::Csv,
+            node_keyword_file: vec![node_keyword_file.clone()],
+            named_keywords: false,
+        };
+        let report = validate(&args).unwrap();
+        let at = |line, path: &Path| format!("at line {} in {}", line, path.display());
+        assert_eq!(
+            report.diagnostics,
+            [
+                format!("invalid node 'x' {}", at(3, &edge_file)),
+                format!("duplicate edge from 1 to 2 {}", at(4, &edge_file)),
+                format!("expect ':' {}", at(2, &node_keyword_file)),
+                format!("empty keyword list {}", at(3, &node_keyword_file)),
+                format!("duplicate node 1 {}", at(4, &node_keyword_file)),
+            ]
+        );
+        assert_eq!(report.errors(), 5);
+        // the build stops at the first of them, with the same message
+        let build = |edge_file: &Path| {
+            build_graph(
+                edge_file,
+                EdgeFormat::Csv,
+                &args.node_keyword_file,
+                false,
+                None,
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+        };
+        assert_eq!(build(&edge_file), Err(report.diagnostics[0].clone()));
+        fs::write(&edge_file, "1,2\n2,3\n").unwrap();
+        assert_eq!(build(&edge_file), Err(report.diagnostics[2].clone()));
+    }
+
+    #[test]
+    fn cache_key_is_stable() {
+        let graph = DiGraphMap::from_edges([(1, 2), (2, 3)]);
+        let node_to_keyword = HashMap::from([(3, vec![10, 20])]);
+        // a saved cache only matches if the key does not change between builds
+        assert_eq!(cache_key(&graph, &node_to_keyword), 0xd080_959e_a83e_ae02);
+        let other = HashMap::from([(3, vec![10])]);
+        assert_ne!(
+            cache_key(&graph, &node_to_keyword),
+            cache_key(&graph, &other)
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Multi-threaded variants of the query primitives, enabled by the `parallel` feature. Work is spread over
+//! [`std::thread::available_parallelism`] scoped threads.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::thread;
+
+use num_traits::bounds::UpperBounded;
+use num_traits::{One, Zero};
+use petgraph::visit::{IntoNeighborsDirected, IntoNodeIdentifiers};
+use petgraph::Direction;
+
+use crate::{
+    keyword_distances_by, keywords_in, partial_cmp, skyline, DistanceMap, Skyline, SkylineError,
+};
+
+fn num_threads() -> usize {
+    thread::available_parallelism().map_or(1, usize::from)
+}
+
+/// Same result as [`skyline`], computed in parallel.
+///
+/// The points are split into one partition per thread and the local skyline of each partition is computed
+/// independently. A point dominated globally is dominated by some local skyline member, so the final pass only
+/// checks the union of the local skylines, again split across threads.
+pub fn skyline_parallel<D, P>(points: &[P]) -> Vec<usize>
+where
+    D: Ord + Sync,
+    P: AsRef<[D]> + Sync,
+{
+    skyline_in_threads(points, num_threads())
+}
+
+/// [`skyline_parallel`] over `num_threads` threads.
+fn skyline_in_threads<D, P>(points: &[P], num_threads: usize) -> Vec<usize>
+where
+    D: Ord + Sync,
+    P: AsRef<[D]> + Sync,
+{
+    if num_threads == 1 {
+        return skyline(points);
+    }
+    let chunk_size = points.len().div_ceil(num_threads).max(1);
+    let candidates: Vec<usize> = thread::scope(|scope| {
+        let handles: Vec<_> = points
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(chunk_idx, chunk)| {
+                scope.spawn(move || {
+                    skyline(chunk)
+                        .into_iter()
+                        .map(|idx| chunk_idx * chunk_size + idx)
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    let candidate_points: Vec<_> = candidates.iter().map(|&idx| points[idx].as_ref()).collect();
+    let chunk_size = candidates.len().div_ceil(num_threads).max(1);
+    thread::scope(|scope| {
+        let candidate_points = &candidate_points;
+        let handles: Vec<_> = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .copied()
+                        .filter(|&idx| {
+                            candidate_points.iter().all(|dv| {
+                                partial_cmp(dv, points[idx].as_ref()) != Some(Ordering::Less)
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// Same result as [`keyword_distances`](crate::keyword_distances), computed in parallel.
+///
+/// The keyword searches are independent, so the keywords are dealt out round-robin to the threads, each filling
+/// its own columns, and the columns are merged into one distance map at the end.
+pub fn keyword_distances_parallel<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> DistanceMap<G::NodeId, D>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected + Send,
+    G::NodeId: Hash + Eq + Send + Sync,
+    K: Ord + Sync,
+    D: Copy + Ord + Zero + One + UpperBounded + Send,
+{
+    let num_threads = num_threads().min(keywords.len()).max(1);
+    let columns: Vec<(usize, DistanceMap<_, D>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..num_threads)
+            .map(|thread_idx| {
+                scope.spawn(move || {
+                    (thread_idx..keywords.len())
+                        .step_by(num_threads)
+                        .map(|keyword_idx| {
+                            // a single-column search for this keyword
+                            let column = keyword_distances_by(
+                                graph,
+                                keywords_in(node_to_keyword),
+                                1,
+                                |_, node_keywords| {
+                                    node_keywords.binary_search(&keywords[keyword_idx]).is_ok()
+                                },
+                                None,
+                                &[Direction::Incoming],
+                            );
+                            (keyword_idx, column)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    let mut dists: DistanceMap<_, _> = graph
+        .node_identifiers()
+        .map(|node| (node, vec![D::max_value(); keywords.len()]))
+        .collect();
+    for (keyword_idx, column) in columns {
+        for (node, dist) in column {
+            dists.get_mut(&node).unwrap()[keyword_idx] = dist[0];
+        }
+    }
+    dists
+}
+
+/// Like [`semantic_place_skyline`](crate::semantic_place_skyline), but the keyword distances are computed by
+/// [`keyword_distances_parallel`] and the skyline by [`skyline_parallel`].
+pub fn semantic_place_skyline_parallel<G, K, D>(
+    graph: G,
+    node_to_keyword: &HashMap<G::NodeId, Vec<K>>,
+    keywords: &[K],
+) -> Result<Skyline<G::NodeId, D>, SkylineError>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected + Send,
+    G::NodeId: Hash + Eq + Send + Sync,
+    K: Ord + Sync,
+    D: Copy + Ord + Zero + One + UpperBounded + Send + Sync,
+{
+    // at least one keyword should be provided
+    if keywords.is_empty() {
+        return Err(SkylineError::EmptyKeywords);
+    }
+    let dists = keyword_distances_parallel(graph, node_to_keyword, keywords);
+    let (nodes, points): (Vec<_>, Vec<_>) = dists.into_iter().unzip();
+    Ok(skyline_parallel(&points)
+        .into_iter()
+        .map(|idx| (nodes[idx], points[idx].clone()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyword_distances;
+    use crate::tests::{random_graph, XorShift};
+
+    #[test]
+    fn skyline_parallel_matches_serial() {
+        let mut rng = XorShift::new(22);
+        for num_points in [0, 1, 7, 100, 500] {
+            let points: Vec<Vec<_>> = (0..num_points)
+                .map(|_| (0..3).map(|_| rng.below(20)).collect())
+                .collect();
+            let expected = skyline(&points);
+            // independent of the available cores, also split the points into several partitions
+            for num_threads in [num_threads(), 1, 2, 3, 8] {
+                let mut result = skyline_in_threads(&points, num_threads);
+                result.sort_unstable();
+                assert_eq!(result, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn keyword_distances_parallel_matches_serial() {
+        let mut rng = XorShift::new(21);
+        for num_keywords in 1..6 {
+            let (graph, node_to_keyword) = random_graph(&mut rng, 80, 200, 5);
+            let keywords: Vec<_> = (0..num_keywords).collect();
+            assert_eq!(
+                keyword_distances_parallel::<_, _, u32>(&graph, &node_to_keyword, &keywords),
+                keyword_distances(&graph, &node_to_keyword, &keywords)
+            );
+        }
+    }
+}
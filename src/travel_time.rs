@@ -0,0 +1,121 @@
+//! Time-valued distances.
+
+use std::ops::Add;
+use std::time::Duration;
+
+use num_traits::bounds::UpperBounded;
+use num_traits::{FromPrimitive, SaturatingAdd, ToPrimitive, Zero};
+
+/// [`Duration`] usable as the distance type of
+/// [`semantic_place_skyline_weighted`](crate::semantic_place_skyline_weighted), with edge costs at full resolution,
+/// and of [`semantic_place_skyline_hop_weighted`](crate::semantic_place_skyline_hop_weighted), with hop weights read
+/// as whole seconds. Unreachable keywords are reported as `Duration::MAX`.
+///
+/// Numeric conversions are in seconds: integer ones truncate the fraction like those of `f64`, `to_f64` and
+/// `from_f64` keep the full resolution.
+///
+/// `Duration` itself cannot implement the `num_traits` bounds from this crate, hence the newtype.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TravelTime(pub Duration);
+
+impl Add for TravelTime {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        TravelTime(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl SaturatingAdd for TravelTime {
+    fn saturating_add(&self, rhs: &Self) -> Self {
+        *self + *rhs
+    }
+}
+
+impl Zero for TravelTime {
+    fn zero() -> Self {
+        TravelTime(Duration::ZERO)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl UpperBounded for TravelTime {
+    fn max_value() -> Self {
+        TravelTime(Duration::MAX)
+    }
+}
+
+/// Converts to seconds.
+impl ToPrimitive for TravelTime {
+    fn to_i64(&self) -> Option<i64> {
+        self.0.as_secs().to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        Some(self.0.as_secs())
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.0.as_secs_f64())
+    }
+}
+
+/// Converts from seconds.
+impl FromPrimitive for TravelTime {
+    fn from_i64(secs: i64) -> Option<Self> {
+        secs.to_u64().and_then(Self::from_u64)
+    }
+
+    fn from_u64(secs: u64) -> Option<Self> {
+        Some(TravelTime(Duration::from_secs(secs)))
+    }
+
+    fn from_f64(secs: f64) -> Option<Self> {
+        Duration::try_from_secs_f64(secs).ok().map(TravelTime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::graphmap::DiGraphMap;
+    use petgraph::visit::EdgeRef;
+
+    use crate::semantic_place_skyline_weighted;
+    use crate::tests::{keywords, sorted};
+
+    #[test]
+    fn weighted_query_over_travel_times() {
+        // 0 -> 1 takes 1.5 s, 1 -> 2 takes 2 s and the shortcut 0 -> 2 takes 4 s
+        let secs = Duration::from_secs_f64;
+        let graph =
+            DiGraphMap::from_edges([(0, 1, secs(1.5)), (1, 2, secs(2.0)), (0, 2, secs(4.0))]);
+        let node_to_keyword = keywords(&[(0, &[9]), (2, &[7])]);
+        let result = semantic_place_skyline_weighted(&graph, &node_to_keyword, &[7, 9], |edge| {
+            TravelTime(*edge.weight())
+        })
+        .unwrap();
+        assert_eq!(
+            sorted(result),
+            vec![
+                (0, vec![TravelTime(secs(3.5)), TravelTime(Duration::ZERO)]),
+                (
+                    2,
+                    vec![TravelTime(Duration::ZERO), TravelTime(Duration::MAX)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn float_conversions_keep_full_resolution() {
+        let time = TravelTime(Duration::from_millis(3500));
+        assert_eq!(time.to_f64(), Some(3.5));
+        assert_eq!(time.to_u64(), Some(3));
+        assert_eq!(TravelTime::from_f64(3.5), Some(time));
+        assert_eq!(TravelTime::from_f64(-1.0), None);
+    }
+}